@@ -1,8 +1,13 @@
 //! A high-level way to load collections of asset handles as resources.
 
-use std::collections::VecDeque;
+use std::{collections::VecDeque, marker::PhantomData};
 
-use bevy::prelude::*;
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use derive_more::derive::{Display, From};
+use serde::de::DeserializeOwned;
 
 pub(super) fn plugin(app: &mut App) {
     app.init_resource::<ResourceHandles>();
@@ -62,3 +67,59 @@ fn load_resource_assets(world: &mut World) {
         });
     });
 }
+
+pub trait LoadRonAsset {
+    /// Registers `T` as an [`Asset`] that is deserialized from RON files ending in `extension`.
+    /// Use a distinct extension per type (e.g. `placements.ron`) so the loader is unambiguous.
+    fn init_ron_asset<T: Asset + DeserializeOwned>(&mut self, extension: &'static str)
+        -> &mut Self;
+}
+
+impl LoadRonAsset for App {
+    fn init_ron_asset<T: Asset + DeserializeOwned>(
+        &mut self,
+        extension: &'static str,
+    ) -> &mut Self {
+        self.init_asset::<T>();
+        self.register_asset_loader(RonAssetLoader::<T> {
+            extensions: [extension],
+            _phantom: PhantomData,
+        })
+    }
+}
+
+struct RonAssetLoader<T> {
+    extensions: [&'static str; 1],
+    _phantom: PhantomData<fn() -> T>,
+}
+
+#[derive(Debug, Display, From)]
+pub enum RonAssetLoaderError {
+    #[display("could not read asset: {_0}")]
+    Io(std::io::Error),
+    #[display("could not parse RON: {_0}")]
+    Ron(ron::error::SpannedError),
+}
+
+impl std::error::Error for RonAssetLoaderError {}
+
+impl<T: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = RonAssetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<T, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
@@ -1,4 +1,28 @@
-use bevy::prelude::*;
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+pub mod sound_bank;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(sound_bank::plugin);
+    app.insert_resource(AudioSettings::load());
+    app.init_resource::<MusicManager>();
+    app.add_systems(
+        Update,
+        (
+            apply_global_volume.run_if(resource_changed::<AudioSettings>),
+            apply_volume,
+            (start_music_tracks, fade_music_tracks).chain(),
+        ),
+    );
+}
 
 /// An organizational marker component that should be added to a spawned [`AudioBundle`] if it is in the
 /// general "music" category (ex: global background music, soundtrack, etc).
@@ -35,3 +59,229 @@ pub struct Music;
 /// ```
 #[derive(Component, Default)]
 pub struct SoundEffect;
+
+/// Volumes chosen by the player in the settings, between 0 and 1.
+/// They apply to every sound, and the music and sound effect volumes additionally to sounds with
+/// the [`Music`] and [`SoundEffect`] markers.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sound_effects: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 0.3,
+            music: 1.0,
+            sound_effects: 1.0,
+        }
+    }
+}
+
+const AUDIO_SETTINGS_KEY: &str = "audio_settings";
+
+impl AudioSettings {
+    /// Read the settings of the last session.
+    pub fn load() -> Self {
+        let Some(text) = storage::read(AUDIO_SETTINGS_KEY) else {
+            return Self::default();
+        };
+        ron::from_str(&text)
+            .map_err(|err| warn!("could not read audio settings: {err}"))
+            .unwrap_or_default()
+    }
+
+    /// Keep the settings for the next session.
+    pub fn save(&self) {
+        let text =
+            ron::ser::to_string_pretty(self, default()).expect("audio settings are serializable");
+        storage::write(AUDIO_SETTINGS_KEY, &text);
+    }
+
+    /// The volume of a sound in the given categories, relative to its [`PlaybackSettings`].
+    pub fn volume(&self, is_music: bool, is_sound_effect: bool) -> f32 {
+        let mut volume = self.master;
+        if is_music {
+            volume *= self.music;
+        }
+        if is_sound_effect {
+            volume *= self.sound_effects;
+        }
+        volume
+    }
+}
+
+/// Sounds start at the global volume, until [`apply_volume`] catches up with them.
+fn apply_global_volume(settings: Res<AudioSettings>, mut global_volume: ResMut<GlobalVolume>) {
+    global_volume.volume = Volume::new(settings.master);
+}
+
+/// Tracks of the [`MusicManager`] are left to [`fade_music_tracks`], which knows how far they faded.
+fn apply_volume(
+    settings: Res<AudioSettings>,
+    sinks: Query<
+        (
+            Ref<AudioSink>,
+            &PlaybackSettings,
+            Has<Music>,
+            Has<SoundEffect>,
+        ),
+        Without<MusicTrack>,
+    >,
+) {
+    for (sink, playback, is_music, is_sound_effect) in &sinks {
+        if !settings.is_changed() && !sink.is_added() {
+            continue;
+        }
+        sink.set_volume(settings.volume(is_music, is_sound_effect) * playback.volume.get());
+    }
+}
+
+/// The independent tracks the [`MusicManager`] can play at the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MusicChannel {
+    /// Songs of the menu screens.
+    Music,
+    /// Background sounds of the area the player is in.
+    Ambience,
+}
+
+/// Plays one track per [`MusicChannel`] and crossfades when a channel switches tracks.
+/// Everything it plays is quieter while it is ducked, e.g. during dialogue.
+#[derive(Resource, Debug)]
+pub struct MusicManager {
+    /// How long it takes for a track to fade in or out.
+    pub crossfade: Duration,
+    /// Volume of the tracks while ducked, relative to their normal volume.
+    pub ducked_volume: f32,
+    tracks: HashMap<MusicChannel, (Handle<AudioSource>, PlaybackMode)>,
+    ducked: bool,
+    duck_level: f32,
+}
+
+impl Default for MusicManager {
+    fn default() -> Self {
+        Self {
+            crossfade: Duration::from_millis(1500),
+            ducked_volume: 0.4,
+            tracks: HashMap::new(),
+            ducked: false,
+            duck_level: 1.0,
+        }
+    }
+}
+
+impl MusicManager {
+    /// Loop `track` on the channel, fading out whatever it played before.
+    pub fn play(&mut self, channel: MusicChannel, track: Handle<AudioSource>) {
+        self.tracks.insert(channel, (track, PlaybackMode::Loop));
+    }
+
+    /// Play `track` on the channel once, fading out whatever it played before.
+    pub fn play_once(&mut self, channel: MusicChannel, track: Handle<AudioSource>) {
+        self.tracks.insert(channel, (track, PlaybackMode::Once));
+    }
+
+    /// Fade out the channel.
+    pub fn stop(&mut self, channel: MusicChannel) {
+        self.tracks.remove(&channel);
+    }
+
+    pub fn set_ducked(&mut self, ducked: bool) {
+        self.ducked = ducked;
+    }
+
+    pub fn is_ducked(&self) -> bool {
+        self.ducked
+    }
+
+    fn is_current(&self, track: &MusicTrack) -> bool {
+        self.tracks
+            .get(&track.channel)
+            .is_some_and(|(handle, _)| *handle == track.handle)
+    }
+}
+
+/// A stop for [`MusicManager`] channels that can be added as a system, e.g. to `OnExit`.
+pub fn stop_music(channel: MusicChannel) -> impl FnMut(ResMut<MusicManager>) + Clone {
+    move |mut manager: ResMut<MusicManager>| manager.stop(channel)
+}
+
+/// A track spawned by the [`MusicManager`].
+#[derive(Component, Debug)]
+struct MusicTrack {
+    channel: MusicChannel,
+    handle: Handle<AudioSource>,
+    /// How far the track has faded in.
+    level: f32,
+}
+
+fn start_music_tracks(
+    mut commands: Commands,
+    manager: Res<MusicManager>,
+    tracks: Query<&MusicTrack>,
+) {
+    for (channel, (handle, mode)) in &manager.tracks {
+        let playing = tracks
+            .iter()
+            .any(|track| track.channel == *channel && track.handle == *handle);
+        if playing {
+            continue;
+        }
+        commands.spawn((
+            Name::new(format!("{channel:?} Track")),
+            MusicTrack {
+                channel: *channel,
+                handle: handle.clone(),
+                level: 0.0,
+            },
+            AudioBundle {
+                source: handle.clone(),
+                settings: PlaybackSettings {
+                    mode: *mode,
+                    volume: Volume::ZERO,
+                    ..default()
+                },
+            },
+            Music,
+        ));
+    }
+}
+
+/// Fades are in real time, so they aren't stuck while the game is paused.
+fn fade_music_tracks(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    settings: Res<AudioSettings>,
+    mut manager: ResMut<MusicManager>,
+    mut tracks: Query<(Entity, &mut MusicTrack, Option<&AudioSink>)>,
+) {
+    let step = time.delta_seconds() / manager.crossfade.as_secs_f32().max(f32::EPSILON);
+    let duck_target = if manager.ducked {
+        manager.ducked_volume
+    } else {
+        1.0
+    };
+    let duck_level = manager.duck_level;
+    if duck_level != duck_target {
+        manager.duck_level = move_towards(duck_level, duck_target, step);
+    }
+
+    for (entity, mut track, sink) in &mut tracks {
+        let target = if manager.is_current(&track) { 1.0 } else { 0.0 };
+        track.level = move_towards(track.level, target, step);
+        if track.level == 0.0 && target == 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if let Some(sink) = sink {
+            sink.set_volume(settings.volume(true, false) * track.level * manager.duck_level);
+        }
+    }
+}
+
+fn move_towards(current: f32, target: f32, max_step: f32) -> f32 {
+    current + (target - current).clamp(-max_step, max_step)
+}
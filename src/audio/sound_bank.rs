@@ -0,0 +1,192 @@
+//! Sounds that are played by name, e.g. from yarn with `<<play_sound name>>`.
+//! They are listed in a sound bank file together with how they should be played.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    audio::Volume,
+    prelude::*,
+};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::asset_tracking::{LoadResource, RonAssetLoaderError};
+
+use super::{Music, SoundEffect};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<SoundBank>();
+    app.register_asset_loader(SoundBankLoader);
+    app.load_resource::<SoundBankAssets>();
+    app.observe(play_sound);
+}
+
+#[derive(Asset, TypePath, Debug)]
+pub struct SoundBank {
+    sounds: HashMap<String, Sound>,
+}
+
+impl SoundBank {
+    pub fn contains(&self, name: &str) -> bool {
+        self.sounds.contains_key(name)
+    }
+
+    /// A bank of silent sound effects with these names.
+    #[cfg(test)]
+    pub fn with_sounds(names: &[&str]) -> Self {
+        let sounds = names
+            .iter()
+            .map(|name| {
+                let sound = Sound {
+                    handle: Handle::default(),
+                    category: SoundCategory::SoundEffect,
+                    volume: 1.0,
+                    pitch_variance: 0.0,
+                };
+                (name.to_string(), sound)
+            })
+            .collect();
+        Self { sounds }
+    }
+}
+
+#[derive(Debug)]
+struct Sound {
+    handle: Handle<AudioSource>,
+    category: SoundCategory,
+    volume: f32,
+    pitch_variance: f32,
+}
+
+/// Which volume setting applies to a sound.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+enum SoundCategory {
+    Music,
+    #[default]
+    SoundEffect,
+}
+
+/// A [`Sound`] as it is written in the sound bank file.
+#[derive(Deserialize)]
+struct SoundDefinition {
+    path: String,
+    #[serde(default)]
+    category: SoundCategory,
+    #[serde(default = "default_volume")]
+    volume: f32,
+    /// How much the playback speed, and with it the pitch, randomly differs between plays.
+    #[serde(default)]
+    pitch_variance: f32,
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+struct SoundBankDefinition {
+    sounds: HashMap<String, SoundDefinition>,
+}
+
+/// Loads the sounds of a sound bank along with it.
+struct SoundBankLoader;
+
+impl AssetLoader for SoundBankLoader {
+    type Asset = SoundBank;
+    type Settings = ();
+    type Error = RonAssetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<SoundBank, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let definition: SoundBankDefinition = ron::de::from_bytes(&bytes)?;
+        let sounds = definition
+            .sounds
+            .into_iter()
+            .map(|(name, sound)| {
+                let sound = Sound {
+                    handle: load_context.load(sound.path),
+                    category: sound.category,
+                    volume: sound.volume,
+                    pitch_variance: sound.pitch_variance,
+                };
+                (name, sound)
+            })
+            .collect();
+        Ok(SoundBank { sounds })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bank.ron"]
+    }
+}
+
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct SoundBankAssets {
+    #[dependency]
+    pub bank: Handle<SoundBank>,
+}
+
+impl SoundBankAssets {
+    pub const PATH_BANK: &'static str = "data/sounds.bank.ron";
+}
+
+impl FromWorld for SoundBankAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            bank: assets.load(SoundBankAssets::PATH_BANK),
+        }
+    }
+}
+
+/// Play the sound with this name from the [`SoundBank`].
+#[derive(Event, Debug)]
+pub struct PlaySound(pub String);
+
+impl PlaySound {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
+
+fn play_sound(
+    trigger: Trigger<PlaySound>,
+    mut commands: Commands,
+    sound_bank_assets: Res<SoundBankAssets>,
+    sound_banks: Res<Assets<SoundBank>>,
+) {
+    let name = &trigger.event().0;
+    let Some(sound) = sound_banks
+        .get(&sound_bank_assets.bank)
+        .and_then(|bank| bank.sounds.get(name))
+    else {
+        error!("unknown sound {name}");
+        return;
+    };
+
+    let speed = if sound.pitch_variance > 0.0 {
+        1.0 + rand::thread_rng().gen_range(-sound.pitch_variance..=sound.pitch_variance)
+    } else {
+        1.0
+    };
+    let mut entity = commands.spawn((
+        AudioBundle {
+            source: sound.handle.clone(),
+            settings: PlaybackSettings::DESPAWN
+                .with_volume(Volume::new(sound.volume))
+                .with_speed(speed),
+        },
+        Name::from(format!("{name} sound")),
+    ));
+    match sound.category {
+        SoundCategory::Music => entity.insert(Music),
+        SoundCategory::SoundEffect => entity.insert(SoundEffect),
+    };
+}
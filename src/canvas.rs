@@ -0,0 +1,106 @@
+//! The world is rendered at a fixed virtual resolution to an offscreen canvas, which is scaled
+//! to the window by whole pixels, so that the pixel art stays crisp at any window size.
+//! The space around the canvas is letterboxed.
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        texture::ImageSampler,
+        view::RenderLayers,
+    },
+    window::PrimaryWindow,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    // Multisampling would blur the edges of the pixel art.
+    app.insert_resource(Msaa::Off);
+    app.init_resource::<Canvas>();
+    app.add_systems(Startup, spawn_canvas);
+    app.add_systems(Update, fit_canvas);
+}
+
+/// Size of the canvas in pixels. All positions in the world are in canvas pixels.
+pub const VIRTUAL_WIDTH: u32 = 1280;
+pub const VIRTUAL_HEIGHT: u32 = 720;
+
+/// The render layer of the canvas sprite, which only the window camera sees.
+pub const CANVAS_LAYER: RenderLayers = RenderLayers::layer(1);
+
+/// The image the world camera renders to.
+#[derive(Resource, Debug)]
+pub struct Canvas {
+    pub image: Handle<Image>,
+}
+
+impl FromWorld for Canvas {
+    fn from_world(world: &mut World) -> Self {
+        let size = Extent3d {
+            width: VIRTUAL_WIDTH,
+            height: VIRTUAL_HEIGHT,
+            ..default()
+        };
+        let mut image = Image {
+            texture_descriptor: TextureDescriptor {
+                label: Some("canvas"),
+                size,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Bgra8UnormSrgb,
+                mip_level_count: 1,
+                sample_count: 1,
+                usage: TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::COPY_DST
+                    | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            },
+            sampler: ImageSampler::nearest(),
+            ..default()
+        };
+        // Fill the image with zeroes.
+        image.resize(size);
+        Self {
+            image: world.resource_mut::<Assets<Image>>().add(image),
+        }
+    }
+}
+
+/// The sprite showing the [`Canvas`] in the window.
+#[derive(Component)]
+struct CanvasSprite;
+
+fn spawn_canvas(mut commands: Commands, canvas: Res<Canvas>) {
+    commands.spawn((
+        Name::new("Canvas"),
+        CanvasSprite,
+        SpriteBundle {
+            texture: canvas.image.clone(),
+            ..default()
+        },
+        CANVAS_LAYER,
+    ));
+}
+
+/// Scale the canvas by the largest whole number that fits it into the window, and the UI with
+/// it, so that it keeps its size relative to the world.
+fn fit_canvas(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut canvas: Query<&mut Transform, With<CanvasSprite>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let fit = (window.width() / VIRTUAL_WIDTH as f32).min(window.height() / VIRTUAL_HEIGHT as f32);
+    // Only windows smaller than the canvas can't show every pixel as a whole number of pixels.
+    let scale = if fit >= 1.0 { fit.floor() } else { fit };
+    for mut transform in &mut canvas {
+        if transform.scale.x != scale {
+            transform.scale = Vec3::new(scale, scale, 1.0);
+        }
+    }
+    if ui_scale.0 != scale {
+        ui_scale.0 = scale;
+    }
+}
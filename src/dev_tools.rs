@@ -5,14 +5,14 @@ use bevy::{
         states::log_transitions,
         ui_debug_overlay::{DebugUiPlugin, UiDebugOptions},
     },
-    input::common_conditions::{input_just_pressed, input_toggle_active},
     prelude::*,
 };
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
-use crate::screens::{Area, Screen};
-
-const INSPECTOR_TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+use crate::{
+    input::{action_just_pressed, action_toggle_active, Action},
+    screens::{Area, Screen},
+};
 
 pub(super) fn plugin(app: &mut App) {
     // Log `Screen` state transitions.
@@ -21,16 +21,14 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_plugins((
         DebugUiPlugin,
-        WorldInspectorPlugin::new().run_if(input_toggle_active(false, INSPECTOR_TOGGLE_KEY)),
+        WorldInspectorPlugin::new().run_if(action_toggle_active(false, Action::ToggleInspector)),
     ));
     app.add_systems(
         Update,
-        toggle_debug_ui.run_if(input_just_pressed(UI_TOGGLE_KEY)),
+        toggle_debug_ui.run_if(action_just_pressed(Action::ToggleUiDebug)),
     );
 }
 
-const UI_TOGGLE_KEY: KeyCode = KeyCode::KeyU;
-
 fn toggle_debug_ui(mut options: ResMut<UiDebugOptions>) {
     options.toggle();
 }
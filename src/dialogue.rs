@@ -1,20 +1,29 @@
+mod validation;
+
 use bevy::prelude::*;
 use bevy_yarnspinner::{
-    events::DialogueCompleteEvent,
-    prelude::{DialogueRunner, YarnFileSource, YarnProject, YarnSpinnerPlugin},
+    events::{DialogueCompleteEvent, PresentOptionsEvent},
+    prelude::{
+        DialogueRunner, OptionId, YarnCommands, YarnFileSource, YarnProject, YarnSpinnerPlugin,
+        YarnSpinnerSystemSet,
+    },
 };
 use bevy_yarnspinner_example_dialogue_view::ExampleYarnSpinnerDialogueViewPlugin;
 
+use self::validation::Argument;
 use crate::{
-    audio::SoundEffect,
+    audio::{sound_bank::PlaySound, MusicManager},
     game::{
-        dino::SpawnDino,
-        inventory::{Inventory, Item},
-        level::Level,
+        cutscene::{Beat, BeatNodes},
+        dino::{SpawnDino, StartDodge},
+        health::{Damage, Heal},
         movement::ActionsFrozen,
-        player::{AutoRunner, Player, PlayerAssets},
+        player::{AutoRunner, Player},
+        recipe::ApplyRecipe,
+        save::LoadedSave,
     },
-    screens::Screen,
+    screens::{Difficulty, Screen},
+    AppSet,
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -31,150 +40,205 @@ pub(super) fn plugin(app: &mut App) {
             YarnFileSource::file("dialogue/banan.yarn"),
             YarnFileSource::file("dialogue/fire.yarn"),
         ]),
-        ExampleYarnSpinnerDialogueViewPlugin::new(),
+        validation::plugin,
     ));
     app.add_systems(OnEnter(Screen::Gameplay), spawn_dialogue_runner);
-    app.add_systems(Update, unfreeze_after_dialog);
+    // Dialogue that the game starts runs in the same frame, and it is answered after the runner,
+    // like the dialogue view does, so that a replay advances it on the same frames.
+    app.configure_sets(Update, YarnSpinnerSystemSet.after(AppSet::Update));
+    app.add_systems(
+        Update,
+        (
+            unfreeze_after_dialog,
+            duck_music_during_dialogue,
+            gamepad_dialogue,
+        )
+            .after(YarnSpinnerSystemSet),
+    );
+}
+
+/// Shows lines and options on screen and lets the player continue or choose with the keyboard and
+/// mouse. Headless tests answer dialogue themselves instead.
+pub(super) fn view_plugin(app: &mut App) {
+    app.add_plugins(ExampleYarnSpinnerDialogueViewPlugin::new());
+}
+
+/// A command that yarn files can run, and the arguments it takes.
+/// Yarn files are checked against these when the game starts.
+struct Command {
+    name: &'static str,
+    arguments: &'static [Argument],
+    /// Adds the command to a dialogue runner. Yarn's own commands have none.
+    register: Option<fn(&mut YarnCommands, &'static str)>,
+}
+
+impl Command {
+    const fn new(
+        name: &'static str,
+        arguments: &'static [Argument],
+        register: fn(&mut YarnCommands, &'static str),
+    ) -> Self {
+        Self {
+            name,
+            arguments,
+            register: Some(register),
+        }
+    }
+
+    const fn builtin(name: &'static str, arguments: &'static [Argument]) -> Self {
+        Self {
+            name,
+            arguments,
+            register: None,
+        }
+    }
+}
+
+const COMMANDS: [Command; 11] = [
+    Command::new("apply_recipe", &[Argument::Recipe], |commands, name| {
+        commands.add_command(name, apply_recipe);
+    }),
+    Command::new("spawn_dino", &[], |commands, name| {
+        commands.add_command(name, spawn_dino);
+    }),
+    Command::new("dodge_dino", &[], |commands, name| {
+        commands.add_command(name, dodge_dino);
+    }),
+    Command::new(
+        "player_run",
+        &[Argument::Direction, Argument::Number],
+        |commands, name| {
+            commands.add_command(name, player_run);
+        },
+    ),
+    Command::new("play_sound", &[Argument::Sound], |commands, name| {
+        commands.add_command(name, play_sound);
+    }),
+    Command::new(
+        "on_beat",
+        &[Argument::Beat, Argument::Node],
+        |commands, name| {
+            commands.add_command(name, on_beat);
+        },
+    ),
+    Command::new("damage", &[Argument::Number], |commands, name| {
+        commands.add_command(name, damage);
+    }),
+    Command::new("heal", &[Argument::Number], |commands, name| {
+        commands.add_command(name, heal);
+    }),
+    Command::new("end_game", &[], |commands, name| {
+        commands.add_command(name, end_game);
+    }),
+    Command::builtin("wait", &[Argument::Number]),
+    Command::builtin("jump", &[Argument::Node]),
+];
+
+/// Which way `<<player_run>>` runs.
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Left,
+    Right,
+}
+
+impl Direction {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "left" => Some(Direction::Left),
+            "right" => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    fn intent(self) -> Vec2 {
+        match self {
+            Direction::Left => Vec2::new(-1.0, 0.0),
+            Direction::Right => Vec2::new(1.0, 0.0),
+        }
+    }
 }
 
 fn spawn_dialogue_runner(
     mut commands: Commands,
     project: Res<YarnProject>,
     mut actions_frozen: ResMut<ActionsFrozen>,
+    loaded_save: Option<Res<LoadedSave>>,
+    difficulty: Res<Difficulty>,
 ) {
     let mut dialogue_runner = project.create_dialogue_runner();
-    dialogue_runner
-        .commands_mut()
-        .add_command("inventory_convert", inventory_convert)
-        .add_command("level_convert", level_convert)
-        .add_command("drop", drop)
-        .add_command("spawn_dino", spawn_dino)
-        .add_command("player_run", player_run)
-        .add_command("play_sound", play_sound)
-        .add_command("end_game", end_game);
-
-    fn inventory_convert(
-        In((from, to)): In<(String, String)>,
-        mut commands: Commands,
-        mut dialogue_runner: Query<&mut DialogueRunner>,
-        mut inventory: ResMut<Inventory>,
-        player_assets: Res<PlayerAssets>,
-    ) {
-        let index = inventory
-            .items
-            .iter()
-            .position(|x| *x.to_string() == from)
-            .unwrap();
-        inventory.items.remove(index);
-        inventory.items.push(Item::from(to.as_str()));
-
-        let mut dialogue_runner = dialogue_runner
-            .get_single_mut()
-            .expect("only one dialogue runner");
-        let vars = dialogue_runner.variable_storage_mut();
-
-        vars.set(format!("$_has_{}", from), false.into()).unwrap();
-        vars.set(format!("$_has_{}", to), true.into()).unwrap();
-
-        commands.spawn((
-            AudioBundle {
-                source: player_assets.item_pickup.clone(),
-                settings: PlaybackSettings::DESPAWN,
-            },
-            SoundEffect,
-            Name::from("Convert sound"),
-        ));
+    if let Err(err) = dialogue_runner
+        .variable_storage_mut()
+        .set("$difficulty".to_string(), difficulty.to_string().into())
+    {
+        error!("could not set $difficulty: {err}");
     }
-
-    fn level_convert(In((from, to)): In<(String, String)>, mut level: ResMut<Level>) {
-        let index = level
-            .items
-            .iter()
-            .position(|x| *x.to_string() == from)
-            .unwrap();
-        level.items.remove(index);
-        level.items.push(Item::from(to.as_str()));
+    for command in &COMMANDS {
+        if let Some(register) = command.register {
+            register(dialogue_runner.commands_mut(), command.name);
+        }
     }
 
-    fn drop(
-        In(item): In<String>,
-        mut commands: Commands,
-        mut dialogue_runner: Query<&mut DialogueRunner>,
-        mut inventory: ResMut<Inventory>,
-        mut level: ResMut<Level>,
-        player_assets: Res<PlayerAssets>,
-    ) {
-        let index = inventory
-            .items
-            .iter()
-            .position(|x| *x.to_string() == item)
-            .unwrap();
-        let item = inventory.items.remove(index);
-        level.items.push(item);
-
-        let mut dialogue_runner = dialogue_runner
-            .get_single_mut()
-            .expect("only one dialogue runner");
-        let vars = dialogue_runner.variable_storage_mut();
-
-        vars.set(format!("$_has_{}", item), false.into()).unwrap();
-
-        commands.spawn((
-            AudioBundle {
-                source: player_assets.item_pickup.clone(),
-                settings: PlaybackSettings::DESPAWN,
-            },
-            SoundEffect,
-            Name::from("Drop sound"),
-        ));
+    // A continued game already saw the intro.
+    if loaded_save.is_none() {
+        dialogue_runner.start_node("Intro");
+        actions_frozen.freeze();
     }
+    commands.spawn((dialogue_runner, StateScoped(Screen::Gameplay)));
+}
 
-    fn player_run(
-        In((direction, end_position)): In<(String, f32)>,
-        mut commands: Commands,
-        player: Query<Entity, With<Player>>,
-    ) {
-        let intent = match direction.as_str() {
-            "left" => Vec2::new(-1.0, 0.0),
-            "right" => Vec2::new(1.0, 0.0),
-            _ => panic!("unknown direction {direction}"),
-        };
-        let entity = player.get_single().expect("exactly one player");
-        commands.entity(entity).insert(AutoRunner {
-            end_position,
-            intent,
-        });
-    }
+fn apply_recipe(In(name): In<String>, mut commands: Commands) {
+    commands.trigger(ApplyRecipe::new(name));
+}
 
-    fn spawn_dino(In(()): In<()>, mut commands: Commands) {
-        commands.trigger(SpawnDino);
-    }
+fn player_run(
+    In((direction, end_position)): In<(String, f32)>,
+    mut commands: Commands,
+    player: Query<Entity, With<Player>>,
+) {
+    let Some(direction) = Direction::from_name(&direction) else {
+        error!("unknown direction {direction}, not running");
+        return;
+    };
+    let Ok(entity) = player.get_single() else {
+        error!("no player to run");
+        return;
+    };
+    commands.entity(entity).insert(AutoRunner {
+        end_position,
+        intent: direction.intent(),
+    });
+}
 
-    fn end_game(In(()): In<()>, mut next_state: ResMut<NextState<Screen>>) {
-        next_state.set(Screen::End);
-    }
+fn spawn_dino(In(()): In<()>, mut commands: Commands) {
+    commands.trigger(SpawnDino);
+}
 
-    fn play_sound(In(name): In<String>, mut commands: Commands, player_assets: Res<PlayerAssets>) {
-        let sound = match name.as_str() {
-            "vine_boom" => player_assets.vine_boom.clone(),
-            "uh_oh" => player_assets.uh_oh.clone(),
-            "trophy_wife" => player_assets.trophy_wife.clone(),
-            "wife_hm" => player_assets.wife_hm.clone(),
-            _ => panic!("unknown sound {name}"),
-        };
-        commands.spawn((
-            AudioBundle {
-                source: sound,
-                settings: PlaybackSettings::DESPAWN,
-            },
-            SoundEffect,
-            Name::from(format!("{name} sound")),
-        ));
-    }
+fn damage(In(amount): In<f32>, mut commands: Commands) {
+    commands.trigger(Damage(amount));
+}
 
-    dialogue_runner.start_node("Intro");
-    commands.spawn((dialogue_runner, StateScoped(Screen::Gameplay)));
-    actions_frozen.freeze();
+fn heal(In(amount): In<f32>, mut commands: Commands) {
+    commands.trigger(Heal(amount));
+}
+
+fn dodge_dino(In(()): In<()>, mut commands: Commands) {
+    commands.trigger(StartDodge);
+}
+
+fn on_beat(In((beat, node)): In<(String, String)>, mut beat_nodes: ResMut<BeatNodes>) {
+    let Some(beat) = Beat::from_name(&beat) else {
+        error!("unknown beat {beat}");
+        return;
+    };
+    beat_nodes.subscribe(beat, node);
+}
+
+fn end_game(In(()): In<()>, mut next_state: ResMut<NextState<Screen>>) {
+    next_state.set(Screen::End);
+}
+
+fn play_sound(In(name): In<String>, mut commands: Commands) {
+    commands.trigger(PlaySound(name));
 }
 
 fn unfreeze_after_dialog(
@@ -185,3 +249,62 @@ fn unfreeze_after_dialog(
         freeze.unfreeze();
     }
 }
+
+fn duck_music_during_dialogue(
+    dialogue_runner: Query<&DialogueRunner>,
+    mut manager: ResMut<MusicManager>,
+) {
+    let talking = dialogue_runner.iter().any(DialogueRunner::is_running);
+    if manager.is_ducked() != talking {
+        manager.set_ducked(talking);
+    }
+}
+
+/// Gamepad face buttons that pick the first, second, third and fourth option.
+pub(crate) const OPTION_BUTTONS: [GamepadButtonType; 4] = [
+    GamepadButtonType::South,
+    GamepadButtonType::East,
+    GamepadButtonType::West,
+    GamepadButtonType::North,
+];
+
+/// The example dialogue view only listens to the keyboard and mouse, so gamepads drive the
+/// dialogue runner themselves: the south button continues and the face buttons pick options.
+fn gamepad_dialogue(
+    mut options: EventReader<PresentOptionsEvent>,
+    mut shown_options: Local<Vec<OptionId>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    mut dialogue_runner: Query<&mut DialogueRunner>,
+) {
+    if let Some(event) = options.read().last() {
+        *shown_options = event.options.iter().map(|option| option.id).collect();
+    }
+    let Ok(mut dialogue_runner) = dialogue_runner.get_single_mut() else {
+        return;
+    };
+    if !dialogue_runner.is_running() {
+        return;
+    }
+
+    for gamepad in gamepads.iter() {
+        let just_pressed =
+            |button_type| buttons.just_pressed(GamepadButton::new(gamepad, button_type));
+        if dialogue_runner.is_waiting_for_option_selection() {
+            let chosen = OPTION_BUTTONS
+                .iter()
+                .position(|button_type| just_pressed(*button_type))
+                .and_then(|index| shown_options.get(index));
+            if let Some(option) = chosen {
+                if let Err(err) = dialogue_runner.select_option(*option) {
+                    error!("could not choose dialogue option: {err}");
+                }
+                return;
+            }
+        } else if just_pressed(GamepadButtonType::South)
+            && !dialogue_runner.will_continue_in_next_update()
+        {
+            dialogue_runner.continue_in_next_update();
+        }
+    }
+}
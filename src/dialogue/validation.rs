@@ -0,0 +1,360 @@
+//! Check the commands of the compiled yarn project once everything they refer to is loaded, so
+//! that a typo in a `.yarn` file is reported with its file and line when the game starts instead
+//! of when the line is reached.
+
+use bevy::prelude::*;
+use bevy_yarnspinner::{prelude::YarnProject, Compilation};
+
+use crate::{
+    audio::sound_bank::{SoundBank, SoundBankAssets},
+    game::{
+        cutscene::Beat,
+        recipe::{RecipeAssets, Recipes},
+    },
+};
+
+use super::{Direction, COMMANDS};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        validate_commands.run_if(
+            resource_exists::<YarnProject>
+                .and_then(resource_exists::<SoundBankAssets>)
+                .and_then(resource_exists::<RecipeAssets>)
+                .and_then(run_once()),
+        ),
+    );
+}
+
+/// The kind of value a yarn command expects as an argument.
+#[derive(Debug, Clone, Copy)]
+pub enum Argument {
+    /// The name of a recipe in the recipes file.
+    Recipe,
+    /// The name of a sound in the sound bank.
+    Sound,
+    /// A [`Direction`].
+    Direction,
+    Number,
+    /// The name of a [`Beat`].
+    Beat,
+    /// The title of a yarn node.
+    Node,
+}
+
+/// A command that the compiled program runs. `<<jump>>` is compiled into running a node, and is
+/// turned back into a command here.
+#[derive(Debug, PartialEq)]
+struct ProgramCommand {
+    file: String,
+    /// Line number, starting at 1, or 0 if the compiler did not record it.
+    line: usize,
+    name: String,
+    arguments: Vec<String>,
+}
+
+/// The commands of every node, ordered by file and line.
+fn program_commands(compilation: &Compilation) -> Vec<ProgramCommand> {
+    let Some(program) = &compilation.program else {
+        return Vec::new();
+    };
+    let mut commands = Vec::new();
+    for (node_name, node) in &program.nodes {
+        for (index, instruction) in node.instructions.iter().enumerate() {
+            let words = match instruction.opcode().as_str_name() {
+                "RUN_COMMAND" => {
+                    let Some(text) = instruction
+                        .operands
+                        .first()
+                        .and_then(|operand| String::try_from(operand.clone()).ok())
+                    else {
+                        continue;
+                    };
+                    split_command(&text)
+                }
+                // A jump pushes the title of the node before running it. Other ways of running a
+                // node, e.g. with a title from a variable, can't be checked.
+                "RUN_NODE" => {
+                    let pushed_title = index
+                        .checked_sub(1)
+                        .map(|previous| &node.instructions[previous])
+                        .filter(|previous| previous.opcode().as_str_name() == "PUSH_STRING")
+                        .and_then(|previous| previous.operands.first())
+                        .and_then(|operand| String::try_from(operand.clone()).ok());
+                    let Some(title) = pushed_title else {
+                        continue;
+                    };
+                    vec!["jump".to_string(), title]
+                }
+                _ => continue,
+            };
+            let Some((name, arguments)) = words.split_first() else {
+                continue;
+            };
+            let line_info = compilation
+                .debug_info
+                .get(node_name)
+                .and_then(|debug_info| debug_info.try_get_line_info(index));
+            commands.push(ProgramCommand {
+                file: line_info
+                    .as_ref()
+                    .map_or_else(|| node_name.clone(), |info| info.file_name.clone()),
+                line: line_info
+                    .and_then(|info| info.position)
+                    .map_or(0, |position| position.line + 1),
+                name: name.clone(),
+                arguments: arguments.to_vec(),
+            });
+        }
+    }
+    commands.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    commands
+}
+
+/// Split the text of a command into words like the dialogue runner does: at whitespace, except
+/// inside double quotes, where `\"` and `\\` are escapes.
+fn split_command(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '"' => {
+                while let Some(char) = chars.next() {
+                    match char {
+                        '"' => break,
+                        '\\' => match chars.clone().next() {
+                            Some(escaped @ ('"' | '\\')) => {
+                                word.push(escaped);
+                                chars.next();
+                            }
+                            _ => word.push(char),
+                        },
+                        _ => word.push(char),
+                    }
+                }
+                words.push(std::mem::take(&mut word));
+            }
+            _ if char.is_whitespace() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            _ => word.push(char),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// A command that can't run as written.
+#[derive(Debug, PartialEq)]
+struct CommandError {
+    file: String,
+    line: usize,
+    name: String,
+    message: String,
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: <<{}>>: {}",
+            self.file, self.line, self.name, self.message
+        )
+    }
+}
+
+fn validate_commands(
+    project: Res<YarnProject>,
+    sound_bank_assets: Res<SoundBankAssets>,
+    sound_banks: Res<Assets<SoundBank>>,
+    recipe_assets: Res<RecipeAssets>,
+    recipes: Res<Assets<Recipes>>,
+) {
+    let (Some(sound_bank), Some(recipes)) = (
+        sound_banks.get(&sound_bank_assets.bank),
+        recipes.get(&recipe_assets.recipes),
+    ) else {
+        return;
+    };
+    let compilation = project.compilation();
+    let commands = program_commands(compilation);
+    let errors = command_errors(compilation, &commands, sound_bank, recipes);
+    for err in &errors {
+        error!("{err}");
+    }
+    if errors.is_empty() {
+        info!("validated {} yarn commands", commands.len());
+    }
+}
+
+/// The `commands` of `compilation` that are unknown or have arguments that don't fit.
+fn command_errors(
+    compilation: &Compilation,
+    commands: &[ProgramCommand],
+    sound_bank: &SoundBank,
+    recipes: &Recipes,
+) -> Vec<CommandError> {
+    let check = |argument: Argument, value: &str| -> Result<(), String> {
+        // Inline expressions are only known once the line runs.
+        if value.starts_with('{') {
+            return Ok(());
+        }
+        let valid = match argument {
+            Argument::Recipe => recipes.get(value).is_some(),
+            Argument::Sound => sound_bank.contains(value),
+            Argument::Direction => Direction::from_name(value).is_some(),
+            Argument::Number => value.parse::<f32>().is_ok(),
+            Argument::Beat => Beat::from_name(value).is_some(),
+            Argument::Node => compilation
+                .program
+                .as_ref()
+                .is_some_and(|program| program.nodes.contains_key(value)),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("\"{value}\" is not a valid {argument:?}"))
+        }
+    };
+
+    commands
+        .iter()
+        .filter_map(|command| {
+            let result = match COMMANDS.iter().find(|known| known.name == command.name) {
+                Some(known) if known.arguments.len() != command.arguments.len() => Err(format!(
+                    "expected {} arguments but got {}",
+                    known.arguments.len(),
+                    command.arguments.len()
+                )),
+                Some(known) => known
+                    .arguments
+                    .iter()
+                    .zip(&command.arguments)
+                    .try_for_each(|(argument, value)| check(*argument, value)),
+                None => Err("unknown command".to_string()),
+            };
+            result.err().map(|message| CommandError {
+                file: command.file.clone(),
+                line: command.line,
+                name: command.name.clone(),
+                message,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::yarn_app;
+
+    #[test]
+    fn split_command_words() {
+        for (text, words) in [
+            ("player_run left 200", vec!["player_run", "left", "200"]),
+            ("  wait   2 ", vec!["wait", "2"]),
+            (
+                "play_sound \"door creak\"",
+                vec!["play_sound", "door creak"],
+            ),
+            (
+                r#"say "a \"quoted\" word""#,
+                vec!["say", r#"a "quoted" word"#],
+            ),
+            (r#"path "C:\\cave""#, vec!["path", r"C:\cave"]),
+            (
+                "say \"unterminated words",
+                vec!["say", "unterminated words"],
+            ),
+            ("empty \"\"", vec!["empty", ""]),
+        ] {
+            assert_eq!(split_command(text), words, "{text}");
+        }
+    }
+
+    fn compile(source: &str) -> Compilation {
+        yarn_app(source)
+            .world()
+            .resource::<YarnProject>()
+            .compilation()
+            .clone()
+    }
+
+    #[test]
+    fn commands_of_compiled_program() {
+        let compilation = compile(
+            "title: Start\n\
+             ---\n\
+             <<wait 1>>\n\
+             // <<commented_out>>\n\
+             <<if true>>\n\
+             Hello.\n\
+             <<endif>>\n\
+             <<player_run right {100 + 100}>>\n\
+             <<jump Other>>\n\
+             ===\n\
+             title: Other\n\
+             ---\n\
+             <<play_sound \"door creak\">>\n\
+             ===\n",
+        );
+        let commands = program_commands(&compilation)
+            .into_iter()
+            .map(|command| (command.line, command.name, command.arguments))
+            .collect::<Vec<_>>();
+        let words = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+        assert_eq!(
+            commands,
+            [
+                (3, "wait".to_string(), words(&["1"])),
+                (8, "player_run".to_string(), words(&["right", "{0}"])),
+                (9, "jump".to_string(), words(&["Other"])),
+                (13, "play_sound".to_string(), words(&["door creak"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_of_mistyped_commands() {
+        let compilation = compile(
+            "title: Start\n\
+             ---\n\
+             <<dance>>\n\
+             <<play_sound>>\n\
+             <<apply_recipe BurnBanana>>\n\
+             <<play_sound door_slam>>\n\
+             <<player_run up 100>>\n\
+             <<jump Missing>>\n\
+             <<apply_recipe CutPapyrus>>\n\
+             <<play_sound item_pickup>>\n\
+             <<on_beat DinoLegLanded Start>>\n\
+             ===\n",
+        );
+        let sound_bank = SoundBank::with_sounds(&["item_pickup"]);
+        let recipes: Recipes =
+            ron::from_str("(recipes: [(name: \"CutPapyrus\", inputs: [], outputs: [])])").unwrap();
+
+        let commands = program_commands(&compilation);
+        let errors = command_errors(&compilation, &commands, &sound_bank, &recipes)
+            .into_iter()
+            .map(|err| err.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                "test.yarn:3: <<dance>>: unknown command",
+                "test.yarn:4: <<play_sound>>: expected 1 arguments but got 0",
+                "test.yarn:5: <<apply_recipe>>: \"BurnBanana\" is not a valid Recipe",
+                "test.yarn:6: <<play_sound>>: \"door_slam\" is not a valid Sound",
+                "test.yarn:7: <<player_run>>: \"up\" is not a valid Direction",
+                "test.yarn:8: <<jump>>: \"Missing\" is not a valid Node",
+            ]
+        );
+    }
+}
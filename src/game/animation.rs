@@ -1,10 +1,16 @@
-use bevy::prelude::*;
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use derive_more::derive::{Display, From};
+use serde::Deserialize;
 
 use crate::{
     audio::SoundEffect,
-    game::{movement::MovementController, player::PlayerAssets},
-    screens::Area,
+    game::{area::Areas, movement::MovementController},
+    screens::{Area, Screen},
     AppSet,
 };
 
@@ -13,18 +19,20 @@ use super::player::Player;
 pub(super) fn plugin(app: &mut App) {
     // Animate and play sound effects based on controls.
     app.register_type::<Animation>();
+    app.init_asset::<AnimationSheet>();
+    app.register_asset_loader(AnimationSheetLoader);
     app.add_systems(
         Update,
         (
             update_animation_timer.in_set(AppSet::TickTimers),
             (
+                apply_animation_sheets,
                 update_animation_movement,
                 update_animation_atlas,
                 trigger_step_sound_effect,
             )
                 .chain()
-                .run_if(resource_exists::<PlayerAssets>)
-                .run_if(resource_exists::<State<Area>>)
+                .run_if(in_state(Screen::Gameplay))
                 .in_set(AppSet::Update),
         ),
     );
@@ -67,7 +75,7 @@ fn update_animation_atlas(mut query: Query<(&Animation, &mut TextureAtlas)>) {
 
 fn trigger_step_sound_effect(
     mut commands: Commands,
-    player_assets: Res<PlayerAssets>,
+    areas: Areas,
     area: Res<State<Area>>,
     mut step_query: Query<&Animation, With<Player>>,
     mut last_area: Local<Area>,
@@ -81,18 +89,18 @@ fn trigger_step_sound_effect(
     }
     *last_area = *area.get();
     for animation in &mut step_query {
-        if animation.state() == AnimationState::Walking {
+        if animation.is_playing(AnimationState::Walking) {
             if sound_entity.is_some() {
                 continue;
             }
+            let Some(definition) = areas.get(*area.get()) else {
+                continue;
+            };
             *sound_entity = Some(
                 commands
                     .spawn((
                         AudioBundle {
-                            source: match area.get() {
-                                Area::Outside => player_assets.run_outside.clone(),
-                                Area::Cave => player_assets.run_cave.clone(),
-                            },
+                            source: definition.steps.clone(),
                             settings: PlaybackSettings::LOOP,
                         },
                         SoundEffect,
@@ -106,21 +114,177 @@ fn trigger_step_sound_effect(
     }
 }
 
+/// A sprite sheet split into a grid of frames, and named clips playing some of them in order.
+/// Sheets are read from `.anim.ron` files, for example:
+///
+/// ```ron
+/// (
+///     tile_size: (16, 23),
+///     columns: 4,
+///     rows: 1,
+///     padding: (2, 2),
+///     offset: (1, 1),
+///     clips: {
+///         "idle": (first: 0, last: 1, durations: [200]),
+///         "walk": (first: 2, last: 3, durations: [100], mode: PingPong),
+///     },
+/// )
+/// ```
+///
+/// `durations` are in milliseconds, either one for every frame of the clip or one for each.
+#[derive(Asset, TypePath, Debug)]
+pub struct AnimationSheet {
+    pub layout: Handle<TextureAtlasLayout>,
+    clips: Vec<AnimationClip>,
+}
+
+#[derive(Reflect, Debug, Clone)]
+struct AnimationClip {
+    name: String,
+    /// Atlas index and duration of each frame, in the order they play.
+    frames: Vec<(usize, Duration)>,
+    /// Whether the clip stops on its last frame instead of starting over.
+    once: bool,
+}
+
+/// How a clip plays its frames.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+enum PlayMode {
+    /// From the first to the last frame, over and over.
+    #[default]
+    Loop,
+    /// From the first to the last frame, and then stay there.
+    Once,
+    /// From the first to the last frame and back, over and over.
+    PingPong,
+}
+
+/// An [`AnimationClip`] as it is written in the sheet file.
+#[derive(Deserialize)]
+struct ClipDefinition {
+    first: usize,
+    last: usize,
+    durations: Vec<u64>,
+    #[serde(default)]
+    mode: PlayMode,
+}
+
+/// An [`AnimationSheet`] as it is written in the sheet file.
+#[derive(Deserialize)]
+struct SheetDefinition {
+    tile_size: UVec2,
+    columns: u32,
+    rows: u32,
+    #[serde(default)]
+    padding: UVec2,
+    #[serde(default)]
+    offset: UVec2,
+    clips: HashMap<String, ClipDefinition>,
+}
+
+#[derive(Debug, Display, From)]
+pub enum AnimationSheetLoaderError {
+    #[display("could not read animation sheet: {_0}")]
+    Io(std::io::Error),
+    #[display("could not parse animation sheet: {_0}")]
+    Ron(ron::error::SpannedError),
+    #[from(skip)]
+    #[display("clip {_0} needs one duration for all of its frames or one for each")]
+    Durations(String),
+    #[from(skip)]
+    #[display("clip {_0} goes past the last frame of the sheet")]
+    OutOfBounds(String),
+}
+
+impl std::error::Error for AnimationSheetLoaderError {}
+
+/// Loads an [`AnimationSheet`] and adds its grid as a `layout` sub-asset.
+struct AnimationSheetLoader;
+
+impl AssetLoader for AnimationSheetLoader {
+    type Asset = AnimationSheet;
+    type Settings = ();
+    type Error = AnimationSheetLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<AnimationSheet, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let definition: SheetDefinition = ron::de::from_bytes(&bytes)?;
+        let frame_count = (definition.columns * definition.rows) as usize;
+
+        let mut clips = Vec::new();
+        for (name, clip) in definition.clips {
+            if clip.first.max(clip.last) >= frame_count {
+                return Err(AnimationSheetLoaderError::OutOfBounds(name));
+            }
+            let mut indices: Vec<usize> = if clip.first <= clip.last {
+                (clip.first..=clip.last).collect()
+            } else {
+                (clip.last..=clip.first).rev().collect()
+            };
+            let durations = match clip.durations.len() {
+                1 => vec![clip.durations[0]; indices.len()],
+                n if n == indices.len() => clip.durations,
+                _ => return Err(AnimationSheetLoaderError::Durations(name)),
+            };
+            if durations.contains(&0) {
+                return Err(AnimationSheetLoaderError::Durations(name));
+            }
+            let mut durations: Vec<Duration> =
+                durations.into_iter().map(Duration::from_millis).collect();
+            if matches!(clip.mode, PlayMode::PingPong) && indices.len() > 2 {
+                // Play back without repeating the last frame, and the first one before looping.
+                let back = indices.len() - 1;
+                let back_indices: Vec<usize> = indices[1..back].iter().rev().copied().collect();
+                let back_durations: Vec<Duration> =
+                    durations[1..back].iter().rev().copied().collect();
+                indices.extend(back_indices);
+                durations.extend(back_durations);
+            }
+            clips.push(AnimationClip {
+                name,
+                frames: indices.into_iter().zip(durations).collect(),
+                once: matches!(clip.mode, PlayMode::Once),
+            });
+        }
+
+        let layout = TextureAtlasLayout::from_grid(
+            definition.tile_size,
+            definition.columns,
+            definition.rows,
+            Some(definition.padding),
+            Some(definition.offset),
+        );
+        Ok(AnimationSheet {
+            layout: load_context.add_labeled_asset("layout".to_string(), layout),
+            clips,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.ron"]
+    }
+}
+
+/// Plays the clips of an [`AnimationSheet`] on a sprite's [`TextureAtlas`].
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct Animation {
-    timer: Timer,
-    frame: usize,
+    sheet: Handle<AnimationSheet>,
+    /// Whether the clips and atlas layout are taken from the current version of the sheet.
+    sheet_applied: bool,
+    clips: Vec<AnimationClip>,
     current: usize,
-    animations: Vec<AnimationData>,
-}
-
-#[derive(Reflect)]
-pub struct AnimationData {
-    pub frames: usize,
-    pub interval: Duration,
-    pub state: AnimationState,
-    pub atlas_index: usize,
+    /// Index into the frames of the current clip.
+    frame: usize,
+    /// Time spent on the current frame.
+    elapsed: Duration,
+    changed: bool,
 }
 
 #[derive(Debug, Reflect, PartialEq, Clone, Copy)]
@@ -129,53 +293,128 @@ pub enum AnimationState {
     Walking,
 }
 
+impl AnimationState {
+    /// The name of the clip that plays in this state.
+    pub fn clip(self) -> &'static str {
+        match self {
+            AnimationState::Idling => "idle",
+            AnimationState::Walking => "walk",
+        }
+    }
+}
+
 impl Animation {
-    pub fn new(animations: Vec<AnimationData>) -> Self {
+    /// Play the `idle` clip of the sheet, or its first one if it has none, as soon as the sheet
+    /// is loaded.
+    pub fn new(sheet: Handle<AnimationSheet>) -> Self {
         Self {
-            timer: Timer::new(animations[0].interval, TimerMode::Repeating),
-            frame: 0,
+            sheet,
+            sheet_applied: false,
+            clips: Vec::new(),
             current: 0,
-            animations,
+            frame: 0,
+            elapsed: Duration::ZERO,
+            changed: true,
         }
     }
 
+    /// Take the clips of a changed sheet, and keep playing the current clip if it still exists.
+    fn reload(&mut self, sheet: &AnimationSheet) {
+        let current = self.clips.get(self.current).map(|clip| clip.name.clone());
+        self.clips.clone_from(&sheet.clips);
+        let playing = current.unwrap_or_else(|| AnimationState::Idling.clip().to_string());
+        self.current = self
+            .clips
+            .iter()
+            .position(|clip| clip.name == playing)
+            .unwrap_or(0);
+        self.frame = 0;
+        self.elapsed = Duration::ZERO;
+        self.changed = true;
+    }
+
     /// Update animation timers.
     pub fn update_timer(&mut self, delta: Duration) {
-        self.timer.tick(delta);
-        if !self.timer.finished() {
+        self.changed = false;
+        let Some(clip) = self.clips.get(self.current) else {
             return;
+        };
+        self.elapsed += delta;
+        loop {
+            let duration = clip.frames[self.frame].1;
+            if self.elapsed < duration {
+                return;
+            }
+            if clip.once && self.frame + 1 == clip.frames.len() {
+                self.elapsed = duration;
+                return;
+            }
+            self.elapsed -= duration;
+            self.frame = (self.frame + 1) % clip.frames.len();
+            self.changed = true;
         }
-        self.frame = (self.frame + 1) % self.animations[self.current].frames;
     }
 
     /// Update animation state if it changes.
     pub fn update_state(&mut self, state: AnimationState) {
-        if self.state() != state {
-            self.current = self
-                .animations
-                .iter()
-                .position(|a| a.state == state)
-                .unwrap();
-
-            let data = &self.animations[self.current];
-
-            self.timer = Timer::new(data.interval, TimerMode::Repeating);
+        if self.is_playing(state) {
+            return;
+        }
+        if let Some(index) = self.clips.iter().position(|clip| clip.name == state.clip()) {
+            self.current = index;
             self.frame = 0;
-            self.update_timer(self.timer.remaining());
+            self.elapsed = Duration::ZERO;
+            self.changed = true;
         }
     }
 
     /// Whether animation changed this tick.
     pub fn changed(&self) -> bool {
-        self.timer.finished()
+        self.changed
     }
 
-    pub fn state(&self) -> AnimationState {
-        self.animations[self.current].state
+    pub fn is_playing(&self, state: AnimationState) -> bool {
+        self.clips
+            .get(self.current)
+            .is_some_and(|clip| clip.name == state.clip())
     }
 
     /// Return sprite index in the atlas.
     pub fn get_atlas_index(&self) -> usize {
-        self.animations[self.current].atlas_index + self.frame
+        self.clips
+            .get(self.current)
+            .map_or(0, |clip| clip.frames[self.frame].0)
+    }
+}
+
+/// Give animations the clips and atlas layout of their sheet, and restart them with the new
+/// clips when the sheet was edited while the game runs. Animations whose sheet is missing stand
+/// still until it loads.
+fn apply_animation_sheets(
+    mut events: EventReader<AssetEvent<AnimationSheet>>,
+    sheets: Res<Assets<AnimationSheet>>,
+    mut animations: Query<(&mut Animation, &mut TextureAtlas)>,
+) {
+    for event in events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        for (mut animation, _) in &mut animations {
+            if animation.sheet.id() == *id {
+                animation.sheet_applied = false;
+            }
+        }
+    }
+    for (mut animation, mut atlas) in &mut animations {
+        if animation.sheet_applied {
+            continue;
+        }
+        let Some(sheet) = sheets.get(&animation.sheet) else {
+            error_once!("animation sheet {:?} is not loaded", animation.sheet.path());
+            continue;
+        };
+        animation.reload(sheet);
+        animation.sheet_applied = true;
+        atlas.layout = sheet.layout.clone();
     }
 }
@@ -0,0 +1,421 @@
+//! The areas of the world and how they connect. Every [`Area`] is described in the area graph
+//! file: what it looks and sounds like, what characters can stand on, and where the player ends
+//! up after walking out of it through its left or right edge.
+//!
+//! The areas themselves are the closed [`Area`] enum, so a new area needs a variant besides its
+//! entry in the file, and a file that misses one fails to load. The characters and things that
+//! always stand in an area, like the wife and the fire, are listed with it as [`Resident`]s. Only
+//! the dino's cutscene is still tied to [`Area::Outside`] in code.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    ecs::system::SystemParam,
+    prelude::*,
+    render::{
+        primitives::Aabb,
+        texture::{ImageLoaderSettings, ImageSampler},
+    },
+};
+use derive_more::derive::{Display, From};
+use serde::Deserialize;
+
+use crate::{
+    asset_tracking::LoadResource,
+    canvas::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH},
+    screens::{Area, Screen},
+};
+
+use super::{camera::Parallax, movement::Platform};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<AreaGraph>();
+    app.register_asset_loader(AreaGraphLoader);
+    app.load_resource::<AreaAssets>();
+
+    app.add_systems(
+        Update,
+        spawn_area.run_if(in_state(Screen::Gameplay).and_then(state_changed::<Area>)),
+    );
+}
+
+#[derive(Asset, TypePath, Debug)]
+pub struct AreaGraph {
+    areas: HashMap<Area, AreaDefinition>,
+}
+
+impl AreaGraph {
+    pub fn get(&self, area: Area) -> Option<&AreaDefinition> {
+        self.areas.get(&area)
+    }
+}
+
+#[derive(Debug)]
+pub struct AreaDefinition {
+    /// Size of the area in world units. The camera scrolls through areas larger than the screen.
+    pub width: f32,
+    pub height: f32,
+    pub background: Layer,
+    pub ground: Layer,
+    /// How far below the top of the ground characters stand, in front of its upper edge.
+    pub ground_sink: f32,
+    /// Sprites in front of the background, e.g. trees.
+    pub props: Vec<Prop>,
+    /// One-way platforms to jump onto.
+    pub platforms: Vec<PlatformDefinition>,
+    /// Spawned whenever the area is entered.
+    pub residents: Vec<ResidentPlacement>,
+    /// Played on the ambience channel while the player is in the area.
+    pub ambience: Handle<AudioSource>,
+    /// Looped while the player walks.
+    pub steps: Handle<AudioSource>,
+    /// Scales how quickly characters speed up and slow down on the ground.
+    pub friction: f32,
+    pub exits: Exits,
+}
+
+#[derive(Debug)]
+pub struct Layer {
+    pub image: Handle<Image>,
+    pub translation: Vec3,
+    pub scale: f32,
+    /// How fast the layer scrolls with the camera, see [`Parallax::factor`].
+    pub parallax: f32,
+}
+
+impl Layer {
+    fn transform(&self) -> Transform {
+        Transform::from_translation(self.translation).with_scale(Vec3::splat(self.scale))
+    }
+
+    fn parallax(&self) -> Parallax {
+        Parallax::new(self.parallax, self.translation.xy())
+    }
+}
+
+#[derive(Debug)]
+pub struct Prop {
+    pub name: String,
+    pub layer: Layer,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlatformDefinition {
+    pub name: String,
+    pub center: Vec2,
+    pub half_size: Vec2,
+}
+
+/// Characters and things that always stand in some area. Each is spawned by its own module when
+/// [`SpawnResident`] is triggered for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Resident {
+    Wife,
+    Fire,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ResidentPlacement {
+    pub resident: Resident,
+    pub translation: Vec3,
+}
+
+/// Spawn `resident` in `area`, which was just entered.
+#[derive(Event, Debug)]
+pub struct SpawnResident {
+    pub resident: Resident,
+    pub area: Area,
+    pub translation: Vec3,
+}
+
+/// Where walking through the left and right edge of the area leads. Edges without an exit are
+/// walls.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+pub struct Exits {
+    #[serde(default)]
+    pub left: Option<Exit>,
+    #[serde(default)]
+    pub right: Option<Exit>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Exit {
+    pub to: Area,
+    /// Where the player appears in the destination area.
+    pub x: f32,
+}
+
+/// A [`Layer`] as it is written in the area graph file.
+#[derive(Deserialize)]
+struct LayerDefinition {
+    image: String,
+    translation: Vec3,
+    #[serde(default = "default_scale")]
+    scale: f32,
+    #[serde(default = "default_parallax")]
+    parallax: f32,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_parallax() -> f32 {
+    1.0
+}
+
+/// A [`Prop`] as it is written in the area graph file.
+#[derive(Deserialize)]
+struct PropDefinition {
+    name: String,
+    image: String,
+    translation: Vec3,
+    #[serde(default = "default_scale")]
+    scale: f32,
+    #[serde(default = "default_parallax")]
+    parallax: f32,
+}
+
+/// An [`AreaDefinition`] as it is written in the area graph file.
+#[derive(Deserialize)]
+struct AreaFileDefinition {
+    #[serde(default = "default_width")]
+    width: f32,
+    #[serde(default = "default_height")]
+    height: f32,
+    background: LayerDefinition,
+    ground: LayerDefinition,
+    #[serde(default)]
+    ground_sink: f32,
+    #[serde(default)]
+    props: Vec<PropDefinition>,
+    #[serde(default)]
+    platforms: Vec<PlatformDefinition>,
+    #[serde(default)]
+    residents: Vec<ResidentPlacement>,
+    ambience: String,
+    steps: String,
+    #[serde(default = "default_friction")]
+    friction: f32,
+    #[serde(default)]
+    exits: Exits,
+}
+
+/// Areas are as large as the window unless they say otherwise.
+fn default_width() -> f32 {
+    VIRTUAL_WIDTH as f32
+}
+
+fn default_height() -> f32 {
+    VIRTUAL_HEIGHT as f32
+}
+
+fn default_friction() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+struct AreaGraphDefinition {
+    areas: HashMap<Area, AreaFileDefinition>,
+}
+
+#[derive(Debug, Display, From)]
+pub enum AreaGraphLoaderError {
+    #[display("could not read area graph: {_0}")]
+    Io(std::io::Error),
+    #[display("could not parse area graph: {_0}")]
+    Ron(ron::error::SpannedError),
+    #[from(skip)]
+    #[display("{_0} is missing from the area graph")]
+    MissingArea(Area),
+}
+
+impl std::error::Error for AreaGraphLoaderError {}
+
+/// Loads the images and sounds of every area along with the graph.
+struct AreaGraphLoader;
+
+impl AssetLoader for AreaGraphLoader {
+    type Asset = AreaGraph;
+    type Settings = ();
+    type Error = AreaGraphLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<AreaGraph, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let definition: AreaGraphDefinition = ron::de::from_bytes(&bytes)?;
+        if let Some(area) = Area::ALL
+            .into_iter()
+            .find(|area| !definition.areas.contains_key(area))
+        {
+            return Err(AreaGraphLoaderError::MissingArea(area));
+        }
+        let mut areas = HashMap::new();
+        for (area, definition) in definition.areas {
+            let area_definition = AreaDefinition {
+                width: definition.width,
+                height: definition.height,
+                background: load_layer(load_context, definition.background),
+                ground: load_layer(load_context, definition.ground),
+                ground_sink: definition.ground_sink,
+                props: definition
+                    .props
+                    .into_iter()
+                    .map(|prop| Prop {
+                        name: prop.name,
+                        layer: load_layer(
+                            load_context,
+                            LayerDefinition {
+                                image: prop.image,
+                                translation: prop.translation,
+                                scale: prop.scale,
+                                parallax: prop.parallax,
+                            },
+                        ),
+                    })
+                    .collect(),
+                platforms: definition.platforms,
+                residents: definition.residents,
+                ambience: load_context.load(definition.ambience),
+                steps: load_context.load(definition.steps),
+                friction: definition.friction,
+                exits: definition.exits,
+            };
+            areas.insert(area, area_definition);
+        }
+        Ok(AreaGraph { areas })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["areas.ron"]
+    }
+}
+
+fn load_layer(load_context: &mut LoadContext, definition: LayerDefinition) -> Layer {
+    Layer {
+        image: load_context
+            .loader()
+            .with_settings(|settings: &mut ImageLoaderSettings| {
+                settings.sampler = ImageSampler::nearest();
+            })
+            .load(definition.image),
+        translation: definition.translation,
+        scale: definition.scale,
+        parallax: definition.parallax,
+    }
+}
+
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct AreaAssets {
+    #[dependency]
+    pub graph: Handle<AreaGraph>,
+}
+
+impl AreaAssets {
+    pub const PATH_GRAPH: &'static str = "data/world.areas.ron";
+}
+
+impl FromWorld for AreaAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            graph: assets.load(AreaAssets::PATH_GRAPH),
+        }
+    }
+}
+
+/// Looks up the [`AreaDefinition`]s once the area graph is loaded, which it is during gameplay.
+#[derive(SystemParam)]
+pub struct Areas<'w> {
+    assets: Res<'w, AreaAssets>,
+    graphs: Res<'w, Assets<AreaGraph>>,
+}
+
+impl Areas<'_> {
+    /// Systems that need the area skip their work without it, e.g. when a hot-reloaded graph
+    /// failed to load.
+    pub fn get(&self, area: Area) -> Option<&AreaDefinition> {
+        self.graphs
+            .get(&self.assets.graph)
+            .and_then(|graph| graph.get(area))
+    }
+}
+
+#[derive(Component)]
+pub struct Background;
+
+#[derive(Component)]
+pub struct Ground;
+
+fn spawn_area(mut commands: Commands, area: Res<State<Area>>, areas: Areas) {
+    let area = *area.get();
+    let Some(definition) = areas.get(area) else {
+        error!("{area} is not in the area graph, not spawning it");
+        return;
+    };
+
+    commands.spawn((
+        Name::new(format!("{area} Background")),
+        Background,
+        SpriteBundle {
+            texture: definition.background.image.clone(),
+            transform: definition.background.transform(),
+            ..default()
+        },
+        definition.background.parallax(),
+        StateScoped(area),
+    ));
+
+    commands.spawn((
+        Name::new(format!("{area} Ground")),
+        Ground,
+        SpriteBundle {
+            texture: definition.ground.image.clone(),
+            transform: definition.ground.transform(),
+            ..default()
+        },
+        definition.ground.parallax(),
+        Platform::solid(definition.ground_sink),
+        StateScoped(area),
+    ));
+
+    for prop in &definition.props {
+        commands.spawn((
+            Name::new(prop.name.clone()),
+            SpriteBundle {
+                texture: prop.layer.image.clone(),
+                transform: prop.layer.transform(),
+                ..default()
+            },
+            prop.layer.parallax(),
+            StateScoped(area),
+        ));
+    }
+
+    for platform in &definition.platforms {
+        commands.spawn((
+            Name::new(platform.name.clone()),
+            Platform::one_way(),
+            Aabb::from_min_max(
+                (-platform.half_size).extend(0.0),
+                platform.half_size.extend(0.0),
+            ),
+            SpatialBundle::from_transform(Transform::from_translation(platform.center.extend(0.0))),
+            StateScoped(area),
+        ));
+    }
+
+    for placement in &definition.residents {
+        commands.trigger(SpawnResident {
+            resident: placement.resident,
+            area,
+            translation: placement.translation,
+        });
+    }
+}
@@ -0,0 +1,126 @@
+//! A camera that follows the player through areas wider than the screen, and sprites that scroll
+//! with it at different rates, so that the background looks farther away than the ground.
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::screens::{Area, Screen};
+
+use super::{area::Areas, player::Player};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<(CameraFollow, Parallax)>();
+    app.add_systems(
+        PostUpdate,
+        (follow_player, apply_parallax)
+            .chain()
+            .before(TransformSystem::TransformPropagate)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        OnExit(Screen::Gameplay),
+        |mut cameras: Query<&mut Transform, With<CameraFollow>>| {
+            for mut transform in &mut cameras {
+                transform.translation.x = 0.0;
+                transform.translation.y = 0.0;
+            }
+        },
+    );
+}
+
+/// Makes a camera follow the player during gameplay, without showing anything outside the
+/// current area.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct CameraFollow {
+    /// How far the player can move away from the center of the view before the camera follows.
+    pub dead_zone: Vec2,
+    /// How quickly the camera catches up with the player. Larger is faster.
+    pub smoothing: f32,
+}
+
+impl Default for CameraFollow {
+    fn default() -> Self {
+        Self {
+            dead_zone: Vec2::new(120.0, 80.0),
+            smoothing: 5.0,
+        }
+    }
+}
+
+/// Scrolls a sprite with the camera. Its translation is set from `origin` every frame.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct Parallax {
+    /// 1 moves with the world, smaller values lag behind it like something farther away, and 0
+    /// stays in place on the screen.
+    pub factor: f32,
+    /// Where the sprite is while the camera is at the origin.
+    pub origin: Vec2,
+}
+
+impl Parallax {
+    pub fn new(factor: f32, origin: Vec2) -> Self {
+        Self { factor, origin }
+    }
+
+    /// Stays in place on the screen, e.g. for the health bar.
+    pub fn fixed(origin: Vec2) -> Self {
+        Self::new(0.0, origin)
+    }
+}
+
+fn follow_player(
+    time: Res<Time>,
+    area: Res<State<Area>>,
+    areas: Areas,
+    player: Query<&Transform, (With<Player>, Without<CameraFollow>)>,
+    mut cameras: Query<(&CameraFollow, &OrthographicProjection, &mut Transform)>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let Some(definition) = areas.get(*area.get()) else {
+        return;
+    };
+    let area_half_size = Vec2::new(definition.width, definition.height) / 2.0;
+    let player_position = player.translation.xy();
+    for (follow, projection, mut transform) in &mut cameras {
+        let position = transform.translation.xy();
+        // After walking into another area, look at the player right away.
+        let entered = area.is_changed();
+        let mut target = if entered { player_position } else { position };
+        // Move just far enough to bring the player back into the dead zone.
+        let offset = player_position - target;
+        target += offset - offset.clamp(-follow.dead_zone, follow.dead_zone);
+        let max = (area_half_size - projection.area.half_size()).max(Vec2::ZERO);
+        target = target.clamp(-max, max);
+
+        let position = if entered {
+            target
+        } else {
+            position.lerp(
+                target,
+                1.0 - (-follow.smoothing * time.delta_seconds()).exp(),
+            )
+        };
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+fn apply_parallax(
+    cameras: Query<&Transform, (With<CameraFollow>, Without<Parallax>)>,
+    mut layers: Query<(&Parallax, &mut Transform)>,
+) {
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+    let camera_position = camera.translation.xy();
+    for (parallax, mut transform) in &mut layers {
+        let position = parallax.origin + camera_position * (1.0 - parallax.factor);
+        if transform.translation.xy() != position {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Named beats of tweened cutscenes, so systems and yarn nodes can react when e.g. the dino leg
+//! touches the ground without agreeing on magic [`TweenCompleted::user_data`] numbers.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_tweening::{AnimationSystem, Tween, TweenCompleted};
+use bevy_yarnspinner::prelude::DialogueRunner;
+use derive_more::derive::Display;
+
+use crate::{screens::Screen, AppSet};
+
+use super::movement::ActionsFrozen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<BeatReached>();
+    app.init_resource::<BeatNodes>();
+    // Beats reach the game's systems in the frame their tween completes.
+    app.add_systems(
+        Update,
+        (
+            emit_beats
+                .after(AnimationSystem::AnimationUpdate)
+                .before(AppSet::Update),
+            start_beat_nodes.in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnEnter(Screen::Gameplay), |mut commands: Commands| {
+        commands.insert_resource(BeatNodes::default())
+    });
+}
+
+/// A moment in a cutscene that is marked by the completion of a tween.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Beat {
+    /// The dino leg touched the ground.
+    DinoLegLanded,
+    /// The dino leg the player had to dodge is out of view again.
+    DinoLegRaised,
+}
+
+impl Beat {
+    const ALL: [Beat; 2] = [Beat::DinoLegLanded, Beat::DinoLegRaised];
+
+    /// Look up a beat by the name used in yarn files.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|beat| beat.to_string() == name)
+    }
+
+    fn user_data(self) -> u64 {
+        // Offset so that zero, the default user data, never maps to a beat.
+        self as u64 + 1
+    }
+
+    fn from_user_data(user_data: u64) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|beat| beat.user_data() == user_data)
+    }
+}
+
+pub trait TweenBeatExt {
+    /// Send a [`BeatReached`] event once this tween completes.
+    fn with_beat(self, beat: Beat) -> Self;
+}
+
+impl<T: 'static> TweenBeatExt for Tween<T> {
+    fn with_beat(self, beat: Beat) -> Self {
+        self.with_completed_event(beat.user_data())
+    }
+}
+
+/// Sent when a tween created with [`TweenBeatExt::with_beat`] completes.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BeatReached {
+    pub beat: Beat,
+    /// The entity whose tween completed.
+    pub entity: Entity,
+}
+
+/// Yarn nodes that are started the next time a [`Beat`] is reached.
+/// Nodes subscribe with `<<on_beat BeatName NodeName>>`.
+#[derive(Resource, Debug, Default)]
+pub struct BeatNodes(HashMap<Beat, Vec<String>>);
+
+impl BeatNodes {
+    pub fn subscribe(&mut self, beat: Beat, node: impl Into<String>) {
+        self.0.entry(beat).or_default().push(node.into());
+    }
+}
+
+fn emit_beats(mut completed: EventReader<TweenCompleted>, mut beats: EventWriter<BeatReached>) {
+    for ev in completed.read() {
+        if let Some(beat) = Beat::from_user_data(ev.user_data) {
+            beats.send(BeatReached {
+                beat,
+                entity: ev.entity,
+            });
+        }
+    }
+}
+
+fn start_beat_nodes(
+    mut beats: EventReader<BeatReached>,
+    mut beat_nodes: ResMut<BeatNodes>,
+    mut dialogue_runner: Query<&mut DialogueRunner>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+) {
+    for ev in beats.read() {
+        let Some(nodes) = beat_nodes.0.remove(&ev.beat) else {
+            continue;
+        };
+        let Ok(mut dialogue_runner) = dialogue_runner.get_single_mut() else {
+            error!("no dialogue runner, not starting the nodes of {}", ev.beat);
+            continue;
+        };
+        for node in nodes {
+            if dialogue_runner.is_running() {
+                warn!("dialogue is running, not starting {node} on {}", ev.beat);
+                continue;
+            }
+            dialogue_runner.start_node(node);
+            actions_frozen.freeze();
+        }
+    }
+}
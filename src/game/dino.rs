@@ -1,19 +1,36 @@
 use std::time::Duration;
 
 use bevy::{
+    math::bounding::{Aabb2d, IntersectsVolume},
     prelude::*,
-    render::texture::{ImageLoaderSettings, ImageSampler},
+    render::{
+        primitives::Aabb,
+        texture::{ImageLoaderSettings, ImageSampler},
+    },
+    sprite::MaterialMesh2dBundle,
+};
+use bevy_tweening::{
+    lens::{TransformPositionLens, TransformScaleLens},
+    Animator, EaseFunction, Tween,
 };
-use bevy_tweening::{lens::TransformPositionLens, Animator, EaseFunction, Tween, TweenCompleted};
 use bevy_yarnspinner::prelude::DialogueRunner;
 
-use crate::{asset_tracking::LoadResource, audio::SoundEffect, screens::Area};
+use crate::{
+    asset_tracking::LoadResource,
+    audio::SoundEffect,
+    screens::{Area, Difficulty},
+    AppSet,
+};
 
 use super::{
+    cutscene::{Beat, BeatReached, TweenBeatExt},
+    health::Damage,
+    interaction::world_aabb2d,
     inventory::{Inventory, Item},
-    level::LevelAssets,
+    level::{Level, LevelAssets},
     movement::ActionsFrozen,
-    player::Player,
+    player::{AutoRunner, Player},
+    recipe::ApplyRecipe,
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -22,9 +39,13 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(
         Update,
-        (start_event, dino_stomp).run_if(in_state(Area::Outside)),
+        (rearm_dodge, start_event, dino_stomp, finish_dodge)
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(in_state(Area::Outside)),
     );
     app.observe(spawn_dino);
+    app.observe(start_dodge);
 }
 
 fn start_event(
@@ -42,9 +63,14 @@ fn start_event(
             return;
         }
     }
-    let mut dialogue_runner = dialogue_runner
-        .get_single_mut()
-        .expect("only one dialogue runner");
+    let Ok(mut dialogue_runner) = dialogue_runner.get_single_mut() else {
+        error!("no dialogue runner, not starting Dino");
+        return;
+    };
+    // The papyrus is only taken once the dialogue gets to it.
+    if dialogue_runner.is_running() {
+        return;
+    }
     dialogue_runner.start_node("Dino");
     actions_frozen.freeze();
 }
@@ -52,22 +78,28 @@ fn start_event(
 #[derive(Event, Debug)]
 pub struct SpawnDino;
 
-fn spawn_dino(_: Trigger<SpawnDino>, mut commands: Commands, dino_assets: Res<DinoAssets>) {
+fn spawn_dino(
+    _: Trigger<SpawnDino>,
+    mut commands: Commands,
+    dino_assets: Res<DinoAssets>,
+    difficulty: Res<Difficulty>,
+) {
+    let duration = Duration::from_millis(1500).div_f32(difficulty.dino_speed());
     let tween = Tween::new(
         EaseFunction::ExponentialIn,
-        Duration::from_millis(1500),
+        duration,
         TransformPositionLens {
-            start: Vec3::new(200.0, 770.0, 1.0),
-            end: Vec3::new(-200.0, 238.0, 0.0),
+            start: Vec3::new(200.0, LEG_RAISED_Y, 1.0),
+            end: Vec3::new(-200.0, LEG_GROUND_Y, 0.0),
         },
     )
-    .with_completed_event(69)
+    .with_beat(Beat::DinoLegLanded)
     .then(Tween::new(
         EaseFunction::ExponentialIn,
-        Duration::from_millis(1500),
+        duration,
         TransformPositionLens {
-            start: Vec3::new(-200.0, 238.0, 0.0),
-            end: Vec3::new(-400.0, 770.0, 1.0),
+            start: Vec3::new(-200.0, LEG_GROUND_Y, 0.0),
+            end: Vec3::new(-400.0, LEG_RAISED_Y, 1.0),
         },
     ));
 
@@ -77,7 +109,7 @@ fn spawn_dino(_: Trigger<SpawnDino>, mut commands: Commands, dino_assets: Res<Di
         SpriteBundle {
             texture: dino_assets.dino_leg.clone(),
             transform: Transform::from_scale(Vec2::splat(8.0).extend(1.0))
-                .with_translation(Vec3::new(200.0, 770.0, 1.0)),
+                .with_translation(Vec3::new(200.0, LEG_RAISED_Y, 1.0)),
             ..Default::default()
         },
         Animator::new(tween),
@@ -85,21 +117,212 @@ fn spawn_dino(_: Trigger<SpawnDino>, mut commands: Commands, dino_assets: Res<Di
     ));
 }
 
+/// Half size of the foot at the bottom of the leg texture, in texture pixels.
+const FOOT_HALF_SIZE: Vec2 = Vec2::new(18.0, 8.0);
+/// Center of the foot relative to the center of the leg texture, in texture pixels.
+const FOOT_OFFSET: Vec2 = Vec2::new(-2.0, -42.0);
+const STOMP_DAMAGE: f32 = 3.0;
+/// Height of the leg's center when the foot touches the ground.
+const LEG_GROUND_Y: f32 = 238.0;
+/// Height of the leg's center when it is out of view.
+const LEG_RAISED_Y: f32 = 770.0;
+const SHADOW_Y: f32 = -160.0;
+/// How far the player is thrown back by a stomp.
+const KNOCKBACK_DISTANCE: f32 = 150.0;
+
 fn dino_stomp(
-    mut reader: EventReader<TweenCompleted>,
+    mut reader: EventReader<BeatReached>,
     mut commands: Commands,
     level_assets: Res<LevelAssets>,
+    mut legs: Query<(&Transform, Option<&mut DodgeStomp>), With<DinoLeg>>,
+    player: Query<(Entity, &Aabb, &Transform), With<Player>>,
+    shadows: Query<Entity, With<StompShadow>>,
 ) {
     for ev in reader.read() {
-        if ev.user_data == 69 {
-            commands.spawn((
-                AudioBundle {
-                    source: level_assets.dino_stomp.clone(),
-                    settings: PlaybackSettings::DESPAWN,
-                },
-                SoundEffect,
-                Name::from("Dino Stomp"),
-            ));
+        if ev.beat != Beat::DinoLegLanded {
+            continue;
+        }
+        let Ok((leg_transform, mut dodge)) = legs.get_mut(ev.entity) else {
+            continue;
+        };
+        let dodging = dodge.is_some();
+
+        let scale = leg_transform.scale.xy();
+        let foot_center = leg_transform.translation.xy() + FOOT_OFFSET * scale;
+        let foot = Aabb2d::new(foot_center, FOOT_HALF_SIZE * scale);
+        let mut hit = false;
+        for (entity, player_aabb, player_transform) in &player {
+            if !foot.intersects(&world_aabb2d(player_aabb, player_transform)) {
+                continue;
+            }
+            hit = true;
+            commands.trigger(Damage(STOMP_DAMAGE));
+            if dodging {
+                let direction = (player_transform.translation.x - foot_center.x).signum();
+                commands.entity(entity).insert(AutoRunner {
+                    end_position: player_transform.translation.x + direction * KNOCKBACK_DISTANCE,
+                    intent: Vec2::new(direction, 0.0),
+                });
+            }
+        }
+
+        if let Some(dodge) = &mut dodge {
+            dodge.hit = hit;
+            for shadow in &shadows {
+                commands.entity(shadow).despawn_recursive();
+            }
+        }
+        // The dino flattens the woven papyrus into paper, unless the player has to dodge again.
+        if !(dodging && hit) {
+            commands.trigger(ApplyRecipe::new("StompPapyrus"));
+        }
+
+        commands.spawn((
+            AudioBundle {
+                source: level_assets.dino_stomp.clone(),
+                settings: PlaybackSettings::DESPAWN,
+            },
+            SoundEffect,
+            Name::from("Dino Stomp"),
+        ));
+    }
+}
+
+/// Start the interactive variant of the dino event, in which the player has to dodge the leg
+/// themselves. The leg keeps stomping until it misses.
+#[derive(Event, Debug)]
+pub struct StartDodge;
+
+/// Marks a leg that the player has to dodge.
+#[derive(Component, Debug, Default)]
+struct DodgeStomp {
+    hit: bool,
+}
+
+/// Telegraphs where the leg of a [`DodgeStomp`] is going to land.
+#[derive(Component, Debug)]
+struct StompShadow;
+
+fn start_dodge(
+    _: Trigger<StartDodge>,
+    mut commands: Commands,
+    dino_assets: Res<DinoAssets>,
+    difficulty: Res<Difficulty>,
+    player: Query<&Transform, With<Player>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let Ok(player_transform) = player.get_single() else {
+        return;
+    };
+    let target_x = player_transform.translation.x;
+    let window = Duration::from_millis(1500).div_f32(difficulty.dino_speed());
+    let scale = 8.0;
+    let leg_x = target_x - FOOT_OFFSET.x * scale;
+
+    commands.spawn((
+        Name::new("Stomp Shadow"),
+        StompShadow,
+        MaterialMesh2dBundle {
+            mesh: meshes
+                .add(Ellipse::new(FOOT_HALF_SIZE.x * scale * 1.2, 14.0))
+                .into(),
+            material: materials.add(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+            transform: Transform::from_translation(Vec3::new(target_x, SHADOW_Y, 0.5))
+                .with_scale(Vec3::splat(0.2)),
+            ..default()
+        },
+        Animator::new(Tween::new(
+            EaseFunction::QuadraticIn,
+            window,
+            TransformScaleLens {
+                start: Vec3::splat(0.2),
+                end: Vec3::ONE,
+            },
+        )),
+        StateScoped(Area::Outside),
+    ));
+
+    let tween = Tween::new(
+        EaseFunction::ExponentialIn,
+        window,
+        TransformPositionLens {
+            start: Vec3::new(leg_x, LEG_RAISED_Y, 1.0),
+            end: Vec3::new(leg_x, LEG_GROUND_Y, 0.0),
+        },
+    )
+    .with_beat(Beat::DinoLegLanded)
+    .then(
+        Tween::new(
+            EaseFunction::ExponentialIn,
+            window,
+            TransformPositionLens {
+                start: Vec3::new(leg_x, LEG_GROUND_Y, 0.0),
+                end: Vec3::new(leg_x, LEG_RAISED_Y, 1.0),
+            },
+        )
+        .with_beat(Beat::DinoLegRaised),
+    );
+
+    commands.spawn((
+        Name::new("Dino Leg"),
+        DinoLeg,
+        DodgeStomp::default(),
+        SpriteBundle {
+            texture: dino_assets.dino_leg.clone(),
+            transform: Transform::from_scale(Vec2::splat(scale).extend(1.0))
+                .with_translation(Vec3::new(leg_x, LEG_RAISED_Y, 1.0)),
+            ..Default::default()
+        },
+        Animator::new(tween),
+        StateScoped(Area::Outside),
+    ));
+}
+
+/// The legs of a dodge are gone after leaving the area, and a continued game starts without them.
+/// The dropped woven papyrus is still waiting for the dino then, so the dodge starts over.
+fn rearm_dodge(
+    mut commands: Commands,
+    level: Res<Level>,
+    legs: Query<(), With<DinoLeg>>,
+    dialogue_runner: Query<&DialogueRunner>,
+    actions_frozen: Res<ActionsFrozen>,
+) {
+    let talking = dialogue_runner.iter().any(DialogueRunner::is_running);
+    if level.items.contains(&Item::WovenPapyrus)
+        && legs.is_empty()
+        && !talking
+        && !actions_frozen.is_frozen()
+    {
+        commands.trigger(StartDodge);
+    }
+}
+
+/// Once the leg is gone, stomp again if it got the player or let the dialogue continue.
+fn finish_dodge(
+    mut reader: EventReader<BeatReached>,
+    mut commands: Commands,
+    legs: Query<&DodgeStomp, With<DinoLeg>>,
+    mut dialogue_runner: Query<&mut DialogueRunner>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+) {
+    for ev in reader.read() {
+        if ev.beat != Beat::DinoLegRaised {
+            continue;
+        }
+        let Ok(dodge) = legs.get(ev.entity) else {
+            continue;
+        };
+        commands.entity(ev.entity).despawn_recursive();
+        if dodge.hit {
+            commands.trigger(StartDodge);
+        } else {
+            let Ok(mut dialogue_runner) = dialogue_runner.get_single_mut() else {
+                error!("no dialogue runner, not starting DinoGone");
+                continue;
+            };
+            dialogue_runner.start_node("DinoGone");
+            actions_frozen.freeze();
         }
     }
 }
@@ -0,0 +1,164 @@
+//! Player health, the things that hurt and the health bar that shows it.
+
+use bevy::{
+    math::bounding::IntersectsVolume, prelude::*, render::primitives::Aabb, sprite::Anchor,
+};
+
+use crate::{
+    screens::{Difficulty, Screen},
+    AppSet,
+};
+
+use super::{
+    fire::Fire,
+    interaction::world_aabb2d,
+    movement::{ActionsFrozen, MovementController},
+    player::Player,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Health>();
+    app.observe(damage_player);
+    app.observe(heal_player);
+    app.add_systems(
+        Update,
+        (
+            tick_invulnerability.in_set(AppSet::TickTimers),
+            (burn_player, die, update_health_bar)
+                .chain()
+                .in_set(AppSet::Update),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+    /// Prevents the same source from hurting the player every frame.
+    invulnerable: Timer,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        let mut invulnerable = Timer::from_seconds(1.0, TimerMode::Once);
+        invulnerable.tick(invulnerable.duration());
+        Self {
+            current: max,
+            max,
+            invulnerable,
+        }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        (self.current / self.max).clamp(0.0, 1.0)
+    }
+}
+
+/// Hurt the player. The amount is scaled by the [`Difficulty`].
+#[derive(Event, Debug)]
+pub struct Damage(pub f32);
+
+/// Give the player back some health.
+#[derive(Event, Debug)]
+pub struct Heal(pub f32);
+
+fn damage_player(
+    trigger: Trigger<Damage>,
+    difficulty: Res<Difficulty>,
+    mut health: Query<&mut Health, With<Player>>,
+) {
+    let amount = trigger.event().0 * difficulty.damage_multiplier();
+    for mut health in &mut health {
+        if !health.invulnerable.finished() {
+            continue;
+        }
+        health.current = (health.current - amount).max(0.0);
+        health.invulnerable.reset();
+    }
+}
+
+fn heal_player(trigger: Trigger<Heal>, mut health: Query<&mut Health, With<Player>>) {
+    for mut health in &mut health {
+        health.current = (health.current + trigger.event().0).min(health.max);
+    }
+}
+
+fn tick_invulnerability(time: Res<Time>, mut health: Query<&mut Health>) {
+    for mut health in &mut health {
+        // Only changes to the actual health should show up in the health bar.
+        health
+            .bypass_change_detection()
+            .invulnerable
+            .tick(time.delta());
+    }
+}
+
+/// Damage per second of standing in the fire.
+const FIRE_DAMAGE: f32 = 1.0;
+
+/// Only standing in the fire burns, the cave can't be crossed without walking through it.
+fn burn_player(
+    mut commands: Commands,
+    actions_frozen: Res<ActionsFrozen>,
+    player: Query<(&Aabb, &Transform, &MovementController), With<Player>>,
+    fire: Query<(&Aabb, &Transform), With<Fire>>,
+) {
+    if actions_frozen.is_frozen() {
+        return;
+    }
+    for (player_aabb, player_transform, controller) in &player {
+        if controller.intent.x != 0.0 {
+            continue;
+        }
+        let player_aabb2d = world_aabb2d(player_aabb, player_transform);
+        for (fire_aabb, fire_transform) in &fire {
+            if player_aabb2d.intersects(&world_aabb2d(fire_aabb, fire_transform)) {
+                commands.trigger(Damage(FIRE_DAMAGE));
+            }
+        }
+    }
+}
+
+fn die(health: Query<&Health, With<Player>>, mut next_screen: ResMut<NextState<Screen>>) {
+    for health in &health {
+        if health.current <= 0.0 {
+            next_screen.set(Screen::GameOver);
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct HealthBar;
+
+/// Width of the heart at the start of the health bar texture, which is always shown.
+const HEALTH_BAR_HEART_WIDTH: f32 = 17.0;
+/// Width of the bar following the heart, which drains with lost health.
+const HEALTH_BAR_FILL_WIDTH: f32 = 37.0;
+const HEALTH_BAR_HEIGHT: f32 = 16.0;
+
+pub fn health_bar_sprite(fraction: f32) -> Sprite {
+    Sprite {
+        anchor: Anchor::CenterLeft,
+        rect: Some(Rect::new(
+            0.0,
+            0.0,
+            HEALTH_BAR_HEART_WIDTH + HEALTH_BAR_FILL_WIDTH * fraction,
+            HEALTH_BAR_HEIGHT,
+        )),
+        ..default()
+    }
+}
+
+fn update_health_bar(
+    health: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut health_bar: Query<&mut Sprite, With<HealthBar>>,
+) {
+    for health in &health {
+        for mut sprite in &mut health_bar {
+            *sprite = health_bar_sprite(health.fraction());
+        }
+    }
+}
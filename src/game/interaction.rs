@@ -0,0 +1,136 @@
+//! Press the interact button to interact with whatever the player is standing next to.
+//! Entities opt in with an [`Interactable`]. When several of them overlap the player, the one
+//! with the highest priority (and then the closest one) is chosen.
+
+use bevy::{
+    math::bounding::{Aabb2d, BoundingVolume, IntersectsVolume},
+    prelude::*,
+    render::primitives::Aabb,
+};
+use bevy_yarnspinner::prelude::DialogueRunner;
+
+use crate::{
+    input::{action_just_pressed, Action},
+    screens::Screen,
+    AppSet,
+};
+
+use super::{movement::ActionsFrozen, player::Player};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<InteractionTarget>();
+    app.add_systems(
+        Update,
+        (
+            find_interaction_target,
+            interact.run_if(action_just_pressed(Action::Interact)),
+        )
+            .chain()
+            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.observe(run_interact_action);
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Interactable {
+    /// Wins over overlapping interactables with a lower priority.
+    pub priority: i32,
+    /// Verb describing the interaction, e.g. "Talk".
+    pub prompt: String,
+    pub action: InteractAction,
+}
+
+#[derive(Debug, Clone)]
+pub enum InteractAction {
+    /// Start the yarn node with this name.
+    StartNode(String),
+    /// Trigger [`PickUp`] on the entity.
+    PickUp,
+    /// Nothing besides [`OnInteract`], for entities that observe it themselves.
+    Custom,
+}
+
+/// Triggered on the chosen [`Interactable`] when the player interacts with it.
+#[derive(Event, Debug)]
+pub struct OnInteract;
+
+/// Triggered on an [`Interactable`] with [`InteractAction::PickUp`] when it is interacted with.
+#[derive(Event, Debug)]
+pub struct PickUp;
+
+/// The [`Interactable`] that would be used if the player interacted right now.
+#[derive(Resource, Debug, Default)]
+pub struct InteractionTarget(pub Option<Entity>);
+
+/// The bounding box of a sprite in world space.
+pub fn world_aabb2d(aabb: &Aabb, transform: &Transform) -> Aabb2d {
+    Aabb2d::new(
+        transform.translation.xy(),
+        aabb.half_extents.xy() * transform.scale.xy(),
+    )
+}
+
+fn find_interaction_target(
+    mut target: ResMut<InteractionTarget>,
+    actions_frozen: Res<ActionsFrozen>,
+    player: Query<(&Aabb, &Transform), With<Player>>,
+    interactables: Query<(Entity, &Aabb, &Transform, &Interactable)>,
+) {
+    let best = if actions_frozen.is_frozen() {
+        None
+    } else {
+        player.get_single().ok().and_then(|(aabb, transform)| {
+            let player_aabb2d = world_aabb2d(aabb, transform);
+            interactables
+                .iter()
+                .map(|(entity, aabb, transform, interactable)| {
+                    (entity, world_aabb2d(aabb, transform), interactable)
+                })
+                .filter(|(_, aabb2d, _)| player_aabb2d.intersects(aabb2d))
+                .max_by(|(_, a, a_interactable), (_, b, b_interactable)| {
+                    let a_distance = a.center().distance_squared(player_aabb2d.center());
+                    let b_distance = b.center().distance_squared(player_aabb2d.center());
+                    a_interactable
+                        .priority
+                        .cmp(&b_interactable.priority)
+                        .then(b_distance.total_cmp(&a_distance))
+                })
+                .map(|(entity, _, _)| entity)
+        })
+    };
+    if target.0 != best {
+        target.0 = best;
+    }
+}
+
+fn interact(mut commands: Commands, target: Res<InteractionTarget>) {
+    if let Some(entity) = target.0 {
+        commands.trigger_targets(OnInteract, entity);
+    }
+}
+
+fn run_interact_action(
+    trigger: Trigger<OnInteract>,
+    mut commands: Commands,
+    interactables: Query<&Interactable>,
+    mut dialogue_runner: Query<&mut DialogueRunner>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+) {
+    let entity = trigger.entity();
+    let Ok(interactable) = interactables.get(entity) else {
+        return;
+    };
+    match &interactable.action {
+        InteractAction::StartNode(node) => {
+            let Ok(mut dialogue_runner) = dialogue_runner.get_single_mut() else {
+                error!("no dialogue runner, not starting {node}");
+                return;
+            };
+            dialogue_runner.start_node(node);
+            actions_frozen.freeze();
+        }
+        InteractAction::PickUp => commands.trigger_targets(PickUp, entity),
+        InteractAction::Custom => {}
+    }
+}
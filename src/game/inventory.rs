@@ -1,22 +1,26 @@
 use bevy::{
     color::palettes::css::BLACK,
-    input::{
-        common_conditions::input_just_pressed,
-        keyboard::{Key, KeyboardInput},
-    },
-    math::bounding::{Aabb2d, IntersectsVolume},
+    input::keyboard::{Key, KeyboardInput},
     prelude::*,
-    render::primitives::Aabb,
 };
 use bevy_yarnspinner::prelude::{DialogueRunner, YarnValue};
 use derive_more::derive::Display;
+use serde::{Deserialize, Serialize};
 
 use super::{
+    camera::Parallax,
+    interaction::PickUp,
     level::{Level, LevelAssets},
     movement::ActionsFrozen,
-    player::{Player, PlayerAssets},
+    player::PlayerAssets,
+    recipe::ApplyRecipe,
+};
+use crate::{
+    audio::sound_bank::PlaySound,
+    input::{action_just_pressed, Action},
+    screens::{is_paused, Screen},
+    theme::prelude::*,
 };
-use crate::{audio::SoundEffect, screens::Screen, theme::prelude::*};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Inventory>();
@@ -25,7 +29,6 @@ pub(super) fn plugin(app: &mut App) {
     app.add_systems(
         Update,
         ((
-            pick_up,
             update_paper_text,
             update_inventory.run_if(resource_changed::<Inventory>),
         )
@@ -33,17 +36,23 @@ pub(super) fn plugin(app: &mut App) {
     );
     app.add_systems(
         Update,
-        close_paper
-            .run_if(in_state(Screen::Gameplay).and_then(input_just_pressed(KeyCode::Escape))),
+        close_paper.run_if(
+            in_state(Screen::Gameplay)
+                .and_then(not(is_paused))
+                .and_then(action_just_pressed(Action::Cancel)),
+        ),
     );
     app.observe(open_paper);
+    app.observe(pick_up);
 
     app.add_systems(OnEnter(Screen::Gameplay), |mut commands: Commands| {
         commands.insert_resource(Inventory::default())
     });
 }
 
-#[derive(Component, Reflect, Debug, Display, Clone, Copy, PartialEq, Eq)]
+#[derive(
+    Component, Reflect, Debug, Display, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
 pub enum Item {
     Papyrus,
     Knife,
@@ -55,22 +64,6 @@ pub enum Item {
     BurntBanana,
 }
 
-impl From<&str> for Item {
-    fn from(name: &str) -> Self {
-        match name {
-            "Papyrus" => Item::Papyrus,
-            "Knife" => Item::Knife,
-            "PapyrusStrips" => Item::PapyrusStrips,
-            "WovenPapyrus" => Item::WovenPapyrus,
-            "Paper" => Item::Paper,
-            "WrittenPaper" => Item::WrittenPaper,
-            "Banana" => Item::Banana,
-            "BurntBanana" => Item::BurntBanana,
-            _ => panic!("unknown item {}", name),
-        }
-    }
-}
-
 #[derive(Resource, Default, Reflect)]
 #[reflect(Resource)]
 pub struct Inventory {
@@ -78,64 +71,39 @@ pub struct Inventory {
 }
 
 fn pick_up(
-    // mut gizmos: Gizmos,
+    trigger: Trigger<PickUp>,
     mut commands: Commands,
-    input: Res<ButtonInput<KeyCode>>,
-    player: Query<(&Aabb, &Transform), With<Player>>,
-    items: Query<(&Aabb, &Transform, &Item)>,
+    items: Query<&Item>,
     mut dialogue_runner: Query<&mut DialogueRunner>,
     mut inventory: ResMut<Inventory>,
     mut level: ResMut<Level>,
-    player_assets: Res<PlayerAssets>,
     mut actions_frozen: ResMut<ActionsFrozen>,
 ) {
-    if actions_frozen.is_frozen() {
+    let Ok(item) = items.get(trigger.entity()) else {
         return;
-    }
-    for (player_aabb, player_transform) in &player {
-        let player_aabb2d = Aabb2d::new(
-            player_transform.translation.xy(),
-            player_aabb.half_extents.xy() * player_transform.scale.xy(),
-        );
-        for (item_aabb, item_transform, item) in &items {
-            let item_aabb2d = Aabb2d::new(
-                item_transform.translation.xy(),
-                item_aabb.half_extents.xy() * item_transform.scale.xy(),
-            );
-            if player_aabb2d.intersects(&item_aabb2d) {
-                if !input.just_pressed(KeyCode::KeyE) {
-                    return;
-                }
-                inventory.items.push(*item);
-
-                if let Some(index) = level.items.iter().position(|x| x == item) {
-                    level.items.remove(index);
-                }
+    };
+    let Ok(mut dialogue_runner) = dialogue_runner.get_single_mut() else {
+        error!("no dialogue runner, not picking up {item}");
+        return;
+    };
+    inventory.items.push(*item);
 
-                commands.spawn((
-                    AudioBundle {
-                        source: player_assets.item_pickup.clone(),
-                        settings: PlaybackSettings::DESPAWN,
-                    },
-                    SoundEffect,
-                    Name::from("Pickup sound"),
-                ));
+    if let Some(index) = level.items.iter().position(|x| x == item) {
+        level.items.remove(index);
+    }
 
-                let mut dialogue_runner = dialogue_runner
-                    .get_single_mut()
-                    .expect("only one dialogue runner");
+    commands.trigger(PlaySound::new("item_pickup"));
 
-                dialogue_runner
-                    .variable_storage_mut()
-                    .set(format!("$_has_{}", item), true.into())
-                    .unwrap();
+    if let Err(err) = dialogue_runner
+        .variable_storage_mut()
+        .set(format!("$_has_{}", item), true.into())
+    {
+        error!("could not update $_has_{item}: {err}");
+    }
 
-                if item == &Item::Paper {
-                    dialogue_runner.start_node("CollectedPaper");
-                    actions_frozen.freeze();
-                }
-            }
-        }
+    if item == &Item::Paper {
+        dialogue_runner.start_node("CollectedPaper");
+        actions_frozen.freeze();
     }
 }
 
@@ -184,12 +152,14 @@ fn interact_item(
     if actions_frozen.is_frozen() {
         return;
     }
-    let item = items
-        .get(trigger.entity())
-        .expect("item was inserted on button");
-    let mut dialogue_runner = dialogue_runner
-        .get_single_mut()
-        .expect("only one dialogue runner");
+    let Ok(item) = items.get(trigger.entity()) else {
+        error!("pressed inventory button without an item");
+        return;
+    };
+    let Ok(mut dialogue_runner) = dialogue_runner.get_single_mut() else {
+        error!("no dialogue runner, not using {item}");
+        return;
+    };
 
     if item == &Item::Paper {
         commands.trigger(OpenPaper);
@@ -203,7 +173,7 @@ fn interact_item(
 struct OpenPaper;
 
 #[derive(Reflect, Component, Debug)]
-struct Paper;
+pub struct Paper;
 
 #[derive(Reflect, Component, Debug)]
 struct PaperText;
@@ -227,6 +197,7 @@ fn open_paper(
                 .with_translation(Vec3::new(0.0, 0.0, 70.0)),
             ..Default::default()
         },
+        Parallax::fixed(Vec2::ZERO),
         StateScoped(Screen::Gameplay),
     ));
     commands.spawn((
@@ -243,6 +214,7 @@ fn open_paper(
             transform: Transform::from_translation(Vec3::new(0.0, 0.0, 80.0)),
             ..default()
         },
+        Parallax::fixed(Vec2::ZERO),
         PaperText,
     ));
     actions_frozen.freeze();
@@ -254,9 +226,9 @@ fn update_paper_text(
     mut text: Query<&mut Text, With<PaperText>>,
     player_assets: Res<PlayerAssets>,
 ) {
-    let dialogue_runner = dialogue_runner
-        .get_single()
-        .expect("only one dialogue runner");
+    let Ok(dialogue_runner) = dialogue_runner.get_single() else {
+        return;
+    };
 
     let learned_pen = dialogue_runner
         .variable_storage()
@@ -308,9 +280,6 @@ fn close_paper(
     paper: Query<Entity, With<Paper>>,
     paper_text: Query<(Entity, &Text), With<PaperText>>,
     mut actions_frozen: ResMut<ActionsFrozen>,
-    mut dialogue_runner: Query<&mut DialogueRunner>,
-    mut inventory: ResMut<Inventory>,
-    player_assets: Res<PlayerAssets>,
 ) {
     for entity in &paper {
         commands.entity(entity).despawn_recursive();
@@ -323,30 +292,7 @@ fn close_paper(
         }
         commands.entity(entity).despawn_recursive();
     }
-    if !written {
-        return;
+    if written {
+        commands.trigger(ApplyRecipe::new("WritePaper"));
     }
-
-    let from = Item::Paper;
-    let to = Item::WrittenPaper;
-    let index = inventory.items.iter().position(|x| *x == from).unwrap();
-    inventory.items.remove(index);
-    inventory.items.push(to);
-
-    let mut dialogue_runner = dialogue_runner
-        .get_single_mut()
-        .expect("only one dialogue runner");
-    let vars = dialogue_runner.variable_storage_mut();
-
-    vars.set(format!("$_has_{}", from), false.into()).unwrap();
-    vars.set(format!("$_has_{}", to), true.into()).unwrap();
-
-    commands.spawn((
-        AudioBundle {
-            source: player_assets.item_pickup.clone(),
-            settings: PlaybackSettings::DESPAWN,
-        },
-        SoundEffect,
-        Name::from("Convert sound"),
-    ));
 }
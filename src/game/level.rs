@@ -5,15 +5,20 @@ use bevy::{
     prelude::*,
     render::texture::{ImageLoaderSettings, ImageSampler},
 };
+use serde::Deserialize;
 
 use crate::{
-    asset_tracking::LoadResource,
+    asset_tracking::{LoadResource, LoadRonAsset},
     screens::{Area, Screen},
 };
 
-use super::{inventory::Item, wife::spawn_wife};
+use super::{
+    interaction::{InteractAction, Interactable},
+    inventory::Item,
+};
 
 pub(super) fn plugin(app: &mut App) {
+    app.init_ron_asset::<ItemPlacements>("placements.ron");
     app.load_resource::<LevelAssets>();
     app.register_type::<Level>();
     app.init_resource::<Level>();
@@ -22,149 +27,77 @@ pub(super) fn plugin(app: &mut App) {
         commands.insert_resource(Level::default())
     });
 
-    app.add_systems(
-        OnEnter(Area::Cave),
-        |mut commands: Commands, level: Res<Level>, items: Query<(Entity, &Item), With<Sprite>>| {
-            commands.add(|w: &mut World| {
-                SpawnBackground { area: Area::Cave }.apply(w);
-                w.run_system_once(spawn_wife);
-            });
-
-            for (item, translation, rotation) in [
-                (
-                    Item::Knife,
-                    Vec3::new(295.0, -130.0, -25.0),
-                    180.0f32.to_radians(),
-                ),
-                (Item::BurntBanana, Vec3::new(0.0, -130.0, 55.0), 0.0),
-            ] {
-                let spawned = items.iter().find(|(_, i)| i == &&item).map(|(e, _)| e);
-                let should_have = level.items.contains(&item);
-                if spawned.is_none() && should_have {
-                    commands.add(move |w: &mut World| {
-                        SpawnItem {
-                            item,
-                            transform: Transform::from_translation(translation)
-                                .with_scale(Vec3::splat(8.0))
-                                .with_rotation(Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, rotation)),
-                        }
-                        .apply(w);
-                    });
-                } else if !should_have {
-                    if let Some(entity) = spawned {
-                        commands.entity(entity).despawn_recursive();
-                    }
-                }
-            }
-        },
-    );
-
     app.add_systems(
         Update,
-        (|mut commands: Commands,
-          level: Res<Level>,
-          items: Query<(Entity, &Item), With<Sprite>>| {
-            for (item, translation, rotation) in [
-                (
-                    Item::Knife,
-                    Vec3::new(295.0, -130.0, -25.0),
-                    180.0f32.to_radians(),
-                ),
-                (Item::BurntBanana, Vec3::new(0.0, -130.0, 55.0), 0.0),
-            ] {
-                let spawned = items.iter().find(|(_, i)| i == &&item).map(|(e, _)| e);
-                let should_have = level.items.contains(&item);
-                if spawned.is_none() && should_have {
-                    commands.add(move |w: &mut World| {
-                        SpawnItem {
-                            item,
-                            transform: Transform::from_translation(translation)
-                                .with_scale(Vec3::splat(8.0))
-                                .with_rotation(Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, rotation)),
-                        }
-                        .apply(w);
-                    });
-                } else if !should_have {
-                    if let Some(entity) = spawned {
-                        commands.entity(entity).despawn_recursive();
-                    }
-                }
-            }
-        })
-        .run_if(resource_changed::<Level>)
-        .run_if(in_state(Area::Cave)),
+        sync_level_items.run_if(
+            in_state(Screen::Gameplay)
+                .and_then(resource_changed::<Level>.or_else(state_changed::<Area>)),
+        ),
     );
+}
 
-    app.add_systems(OnEnter(Area::Outside), |mut commands: Commands| {
-        commands.add(|w: &mut World| {
-            SpawnBackground {
-                area: Area::Outside,
-            }
-            .apply(w);
-        });
-    });
+/// Where each [`Item`] lies around the world while it is part of the [`Level`].
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct ItemPlacements {
+    pub placements: Vec<ItemPlacement>,
+}
 
-    app.add_systems(
-        Update,
-        (|mut commands: Commands,
-          level: Res<Level>,
-          items: Query<(Entity, &Item), With<Sprite>>| {
-            for (item, translation) in [
-                (Item::WovenPapyrus, Vec3::new(-300.0, -130.0, -25.0)),
-                (Item::Paper, Vec3::new(-300.0, -130.0, -25.0)),
-                (Item::Papyrus, Vec3::new(170.0, -62.0, -25.0)),
-                (Item::Banana, Vec3::new(440.0, -130.0, -25.0)),
-            ] {
-                let spawned = items.iter().find(|(_, i)| i == &&item).map(|(e, _)| e);
-                let should_have = level.items.contains(&item);
-                if spawned.is_none() && should_have {
-                    commands.add(move |w: &mut World| {
-                        SpawnItem {
-                            item,
-                            transform: Transform::from_translation(translation)
-                                .with_scale(Vec3::splat(8.0)),
-                        }
-                        .apply(w);
-                    });
-                } else if !should_have {
-                    if let Some(entity) = spawned {
-                        commands.entity(entity).despawn_recursive();
-                    }
-                }
-            }
-        })
-        .run_if(resource_changed::<Level>)
-        .run_if(in_state(Area::Outside)),
-    );
+#[derive(Debug, Deserialize)]
+pub struct ItemPlacement {
+    pub item: Item,
+    pub area: Area,
+    pub translation: Vec3,
+    /// Rotation around the z axis in degrees.
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default = "ItemPlacement::default_scale")]
+    pub scale: f32,
+}
 
-    app.add_systems(
-        OnEnter(Area::Outside),
-        |mut commands: Commands, level: Res<Level>, items: Query<(Entity, &Item), With<Sprite>>| {
-            for (item, translation) in [
-                (Item::WovenPapyrus, Vec3::new(-300.0, -130.0, -25.0)),
-                (Item::Paper, Vec3::new(-300.0, -130.0, -25.0)),
-                (Item::Papyrus, Vec3::new(170.0, -62.0, -25.0)),
-                (Item::Banana, Vec3::new(440.0, -130.0, -25.0)),
-            ] {
-                let spawned = items.iter().find(|(_, i)| i == &&item).map(|(e, _)| e);
-                let should_have = level.items.contains(&item);
-                if spawned.is_none() && should_have {
-                    commands.add(move |w: &mut World| {
-                        SpawnItem {
-                            item,
-                            transform: Transform::from_translation(translation)
-                                .with_scale(Vec3::splat(8.0)),
-                        }
-                        .apply(w);
-                    });
-                } else if !should_have {
-                    if let Some(entity) = spawned {
-                        commands.entity(entity).despawn_recursive();
-                    }
-                }
+impl ItemPlacement {
+    fn default_scale() -> f32 {
+        8.0
+    }
+
+    fn transform(&self) -> Transform {
+        Transform::from_translation(self.translation)
+            .with_scale(Vec3::splat(self.scale))
+            .with_rotation(Quat::from_rotation_z(self.rotation.to_radians()))
+    }
+}
+
+/// Spawn the sprites of items that are in the [`Level`] and placed in the current [`Area`],
+/// and despawn the ones that have been taken out of it.
+fn sync_level_items(
+    mut commands: Commands,
+    level: Res<Level>,
+    area: Res<State<Area>>,
+    level_assets: Res<LevelAssets>,
+    placements: Res<Assets<ItemPlacements>>,
+    items: Query<(Entity, &Item), With<Sprite>>,
+) {
+    let Some(placements) = placements.get(&level_assets.item_placements) else {
+        return;
+    };
+    for placement in placements
+        .placements
+        .iter()
+        .filter(|p| p.area == *area.get())
+    {
+        let item = placement.item;
+        let spawned = items.iter().find(|(_, i)| **i == item).map(|(e, _)| e);
+        let should_have = level.items.contains(&item);
+        if spawned.is_none() && should_have {
+            let transform = placement.transform();
+            commands.add(move |w: &mut World| {
+                SpawnItem { item, transform }.apply(w);
+            });
+        } else if !should_have {
+            if let Some(entity) = spawned {
+                commands.entity(entity).despawn_recursive();
             }
-        },
-    );
+        }
+    }
 }
 
 #[derive(Resource, Reflect)]
@@ -183,17 +116,6 @@ impl Default for Level {
 
 #[derive(Resource, Asset, Reflect, Clone)]
 pub struct LevelAssets {
-    #[dependency]
-    pub cave_background: Handle<Image>,
-    #[dependency]
-    pub cave_ground: Handle<Image>,
-    #[dependency]
-    pub outside_background: Handle<Image>,
-    #[dependency]
-    pub outside_ground: Handle<Image>,
-    #[dependency]
-    pub palm_tree: Handle<Image>,
-
     #[dependency]
     pub knife: Handle<Image>,
     #[dependency]
@@ -213,14 +135,12 @@ pub struct LevelAssets {
 
     #[dependency]
     pub dino_stomp: Handle<AudioSource>,
+
+    #[dependency]
+    pub item_placements: Handle<ItemPlacements>,
 }
 
 impl LevelAssets {
-    pub const PATH_CAVE_BACKGROUND: &'static str = "images/cave.png";
-    pub const PATH_CAVE_GROUND: &'static str = "images/cave_ground.png";
-    pub const PATH_OUTSIDE_BACKGROUND: &'static str = "images/outside.png";
-    pub const PATH_OUTSIDE_GROUND: &'static str = "images/outside_ground.png";
-    pub const PATH_PALM_TREE: &'static str = "images/palm_tree.png";
     pub const PATH_PAPYRUS: &'static str = "images/papyrus.png";
     pub const PATH_KNIFE: &'static str = "images/knife.png";
     pub const PATH_WOVEN: &'static str = "images/papyrus_woven.png";
@@ -230,42 +150,13 @@ impl LevelAssets {
     pub const PATH_BANANA: &'static str = "images/banan.png";
     pub const PATH_BURNT_BANANA: &'static str = "images/banan_burnt.png";
     pub const PATH_DINO_STOMP: &'static str = "audio/sound_effects/stomp.ogg";
+    pub const PATH_ITEM_PLACEMENTS: &'static str = "data/items.placements.ron";
 }
 
 impl FromWorld for LevelAssets {
     fn from_world(world: &mut World) -> Self {
         let assets = world.resource::<AssetServer>();
         Self {
-            cave_background: assets.load_with_settings(
-                LevelAssets::PATH_CAVE_BACKGROUND,
-                |settings: &mut ImageLoaderSettings| {
-                    settings.sampler = ImageSampler::nearest();
-                },
-            ),
-            cave_ground: assets.load_with_settings(
-                LevelAssets::PATH_CAVE_GROUND,
-                |settings: &mut ImageLoaderSettings| {
-                    settings.sampler = ImageSampler::nearest();
-                },
-            ),
-            outside_background: assets.load_with_settings(
-                LevelAssets::PATH_OUTSIDE_BACKGROUND,
-                |settings: &mut ImageLoaderSettings| {
-                    settings.sampler = ImageSampler::nearest();
-                },
-            ),
-            outside_ground: assets.load_with_settings(
-                LevelAssets::PATH_OUTSIDE_GROUND,
-                |settings: &mut ImageLoaderSettings| {
-                    settings.sampler = ImageSampler::nearest();
-                },
-            ),
-            palm_tree: assets.load_with_settings(
-                LevelAssets::PATH_PALM_TREE,
-                |settings: &mut ImageLoaderSettings| {
-                    settings.sampler = ImageSampler::nearest();
-                },
-            ),
             papyrus: assets.load_with_settings(
                 LevelAssets::PATH_PAPYRUS,
                 |settings: &mut ImageLoaderSettings| {
@@ -315,84 +206,11 @@ impl FromWorld for LevelAssets {
                 },
             ),
             dino_stomp: assets.load(LevelAssets::PATH_DINO_STOMP),
+            item_placements: assets.load(LevelAssets::PATH_ITEM_PLACEMENTS),
         }
     }
 }
 
-#[derive(Debug)]
-pub struct SpawnBackground {
-    area: Area,
-}
-
-impl Command for SpawnBackground {
-    fn apply(self, world: &mut World) {
-        world.run_system_once_with(self, spawn_background);
-    }
-}
-
-#[derive(Component)]
-pub struct Background;
-
-#[derive(Component)]
-pub struct Ground;
-
-fn spawn_background(
-    In(config): In<SpawnBackground>,
-    mut commands: Commands,
-    level_assets: Res<LevelAssets>,
-) {
-    commands.spawn((
-        Name::new(format!("{} Background", config.area)),
-        Background,
-        SpriteBundle {
-            texture: match config.area {
-                Area::Cave => level_assets.cave_background.clone(),
-                Area::Outside => level_assets.outside_background.clone(),
-            },
-            transform: match config.area {
-                Area::Cave => Transform::from_translation(Vec3::new(0.0, 200.0, -50.0))
-                    .with_scale(Vec3::splat(0.8)),
-                Area::Outside => Transform::from_translation(Vec3::new(0.0, 160.0, -50.0))
-                    .with_scale(Vec3::splat(2.0)),
-            },
-            ..Default::default()
-        },
-        StateScoped(config.area),
-    ));
-
-    commands.spawn((
-        Name::new(format!("{} Ground", config.area)),
-        Ground,
-        SpriteBundle {
-            texture: match config.area {
-                Area::Cave => level_assets.cave_ground.clone(),
-                Area::Outside => level_assets.outside_ground.clone(),
-            },
-            transform: match config.area {
-                Area::Cave => Transform::from_translation(Vec3::new(0.0, -264.0, 40.0))
-                    .with_scale(Vec3::splat(1.0)),
-                Area::Outside => Transform::from_translation(Vec3::new(0.0, -255.0, 50.0))
-                    .with_scale(Vec3::splat(1.0)),
-            },
-            ..Default::default()
-        },
-        StateScoped(config.area),
-    ));
-
-    if config.area == Area::Outside {
-        commands.spawn((
-            Name::new("Palm Tree"),
-            SpriteBundle {
-                texture: level_assets.palm_tree.clone(),
-                transform: Transform::from_translation(Vec3::new(480.0, 95.0, -40.0))
-                    .with_scale(Vec3::splat(8.0)),
-                ..Default::default()
-            },
-            StateScoped(config.area),
-        ));
-    }
-}
-
 #[derive(Debug)]
 pub struct SpawnItem {
     item: Item,
@@ -427,6 +245,13 @@ fn spawn_item(
             transform: config.transform,
             ..Default::default()
         },
+        Interactable {
+            // Items lie around the fire and the wife, but picking them up is what the player
+            // is most likely after.
+            priority: 2,
+            prompt: "Pick up".to_string(),
+            action: InteractAction::PickUp,
+        },
         StateScoped(*state.get()),
     ));
 }
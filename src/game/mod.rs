@@ -5,24 +5,38 @@
 
 use bevy::prelude::*;
 
-mod animation;
+pub mod animation;
+pub mod area;
+pub mod camera;
+pub mod cutscene;
 pub mod dino;
 pub mod fire;
+pub mod health;
+pub mod interaction;
 pub mod inventory;
 pub mod level;
 pub mod movement;
 pub mod player;
-mod wife;
+mod prompt;
+pub mod recipe;
+pub mod save;
+pub mod wife;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         animation::plugin,
+        area::plugin,
+        camera::plugin,
+        cutscene::plugin,
         movement::plugin,
         player::plugin,
+        prompt::plugin,
         level::plugin,
         inventory::plugin,
         wife::plugin,
         dino::plugin,
         fire::plugin,
+        health::plugin,
+        (interaction::plugin, recipe::plugin, save::plugin),
     ));
 }
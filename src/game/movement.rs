@@ -6,31 +6,44 @@
 //! - Set [`MovementController`] intent based on directional keyboard input.
 //!   This is done in the `player` module, as it is specific to the player
 //!   character.
-//! - Apply movement based on [`MovementController`] intent and maximum speed.
-//! - Wrap the character within the window.
-//!
-//! Note that the implementation used here is limited for demonstration
-//! purposes. If you want to move the player in a smoother way,
-//! consider using a [fixed timestep](https://github.com/bevyengine/bevy/blob/main/examples/movement/physics_in_fixed_timestep.rs).
+//! - Accelerate towards [`MovementController`] intent and maximum speed, and
+//!   slow down on the ground of the current area without it.
+//!   This runs on a fixed timestep, so characters end up in the same place no
+//!   matter the frame rate, e.g. when a recorded game is replayed.
+//! - Jump when asked to while standing, fall with gravity and land on
+//!   [`Platform`]s.
+//! - Interpolate the [`Transform`] between the last two fixed steps, so
+//!   movement still looks smooth when frames don't line up with them.
+//! - Keep the player within the area, or walk into the next one through its edges.
 
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::{prelude::*, render::primitives::Aabb};
 
 use crate::{
     screens::{Area, Screen},
+    transition::{Transition, TransitionSettings},
     AppSet,
 };
 
-use super::player::Player;
+use super::{
+    area::{Areas, Exit},
+    interaction::world_aabb2d,
+    player::Player,
+};
 
 pub(super) fn plugin(app: &mut App) {
-    app.register_type::<MovementController>();
+    app.register_type::<(MovementController, Platform)>();
     app.init_resource::<ActionsFrozen>();
 
     app.add_systems(
-        Update,
+        FixedUpdate,
         (apply_movement, clamp_player_x, change_level)
             .chain()
-            .in_set(AppSet::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        Update,
+        interpolate_movement
+            .before(AppSet::TickTimers)
             .run_if(in_state(Screen::Gameplay)),
     );
 
@@ -45,69 +58,246 @@ pub(super) fn plugin(app: &mut App) {
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct MovementController {
-    /// The direction the character wants to move in.
+    /// The direction the character wants to move in. Only `x` is used, characters move up by
+    /// jumping.
     pub intent: Vec2,
 
+    /// Set to jump on the next fixed step, if the character stands on something by then.
+    pub jump: bool,
+
     /// Maximum speed in world units per second.
     /// 1 world unit = 1 pixel when using the default 2D camera and no physics
     /// engine.
     pub max_speed: f32,
+
+    /// How quickly the character speeds up towards its intent, in world units
+    /// per second squared.
+    pub acceleration: f32,
+
+    /// How quickly the character slows down without intent or when turning
+    /// around, in world units per second squared.
+    pub deceleration: f32,
+
+    /// Upwards speed at the start of a jump, in world units per second.
+    pub jump_speed: f32,
+
+    /// How quickly the character falls faster, in world units per second squared.
+    pub gravity: f32,
+
+    /// Current velocity in world units per second.
+    pub velocity: Vec2,
+
+    /// Whether the character landed on a [`Platform`] in the last fixed step.
+    pub grounded: bool,
+
+    /// Positions after the last two fixed steps.
+    pub(super) previous_position: Vec2,
+    pub(super) position: Vec2,
+    /// The interpolated position that was last written to the [`Transform`],
+    /// to notice when something else moved the character.
+    pub(super) rendered_position: Option<Vec2>,
 }
 
 impl Default for MovementController {
     fn default() -> Self {
         Self {
             intent: Vec2::ZERO,
+            jump: false,
             // 400 pixels per second is a nice default, but we can still vary this per character.
             max_speed: 400.0,
+            acceleration: 2400.0,
+            deceleration: 3000.0,
+            jump_speed: 850.0,
+            gravity: 2400.0,
+            velocity: Vec2::ZERO,
+            grounded: false,
+            previous_position: Vec2::ZERO,
+            position: Vec2::ZERO,
+            rendered_position: None,
         }
     }
 }
 
+/// Something characters can stand on. Its surface is the top of its [`Aabb`].
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct Platform {
+    /// One-way platforms can be jumped through from below and only carry characters that land
+    /// on them from above. Characters below solid ones are pushed up onto them.
+    pub one_way: bool,
+    /// How far below the top of the bounds characters stand, e.g. in the grass of a ground sprite.
+    pub sink: f32,
+}
+
+impl Platform {
+    pub fn solid(sink: f32) -> Self {
+        Self {
+            one_way: false,
+            sink,
+        }
+    }
+
+    pub fn one_way() -> Self {
+        Self {
+            one_way: true,
+            sink: 0.0,
+        }
+    }
+}
+
+/// Feet that end up this close above a surface still stand on it.
+const LANDING_TOLERANCE: f32 = 0.5;
+
 fn apply_movement(
     time: Res<Time>,
-    mut movement_query: Query<(&MovementController, &mut Transform)>,
+    area: Res<State<Area>>,
+    areas: Areas,
+    mut movement_query: Query<(&mut MovementController, &Transform, Option<&Aabb>)>,
+    platforms: Query<(&Platform, &Aabb, &Transform)>,
 ) {
-    for (controller, mut transform) in &mut movement_query {
-        let velocity = controller.max_speed * controller.intent;
-        transform.translation += velocity.extend(0.0) * time.delta_seconds();
+    let delta = time.delta_seconds();
+    // Without the area, characters move like on plain ground.
+    let friction = areas
+        .get(*area.get())
+        .map_or(1.0, |definition| definition.friction);
+    let surfaces: Vec<_> = platforms
+        .iter()
+        .map(|(platform, aabb, transform)| {
+            let bounds = world_aabb2d(aabb, transform);
+            (
+                platform,
+                bounds.min.x..=bounds.max.x,
+                bounds.max.y - platform.sink,
+            )
+        })
+        .collect();
+
+    for (mut controller, transform, aabb) in &mut movement_query {
+        let target = controller.max_speed * controller.intent.x;
+        let speeding_up = target != 0.0 && target * controller.velocity.x >= 0.0;
+        let rate = if speeding_up {
+            controller.acceleration
+        } else {
+            controller.deceleration
+        } * friction;
+        let velocity = controller.velocity.x;
+        controller.velocity.x += (target - velocity).clamp(-rate * delta, rate * delta);
+
+        if std::mem::take(&mut controller.jump) && controller.grounded {
+            controller.velocity.y = controller.jump_speed;
+        }
+        controller.velocity.y -= controller.gravity * delta;
+
+        controller.previous_position = controller.position;
+        let velocity = controller.velocity;
+        controller.position += velocity * delta;
+
+        // Land on the highest surface the feet went through.
+        controller.grounded = false;
+        let Some(aabb) = aabb else {
+            continue;
+        };
+        let feet = -aabb.half_extents.y * transform.scale.y;
+        let previous_feet = controller.previous_position.y + feet;
+        let new_feet = controller.position.y + feet;
+        let x = controller.position.x;
+        let landing = surfaces
+            .iter()
+            .filter(|(platform, span, top)| {
+                span.contains(&x)
+                    && new_feet <= *top
+                    && (!platform.one_way || previous_feet >= *top - LANDING_TOLERANCE)
+            })
+            .map(|(_, _, top)| *top)
+            .max_by(f32::total_cmp);
+        if let Some(top) = landing {
+            if controller.velocity.y <= 0.0 {
+                controller.position.y = top - feet;
+                controller.velocity.y = 0.0;
+                controller.grounded = true;
+            }
+        }
+    }
+}
+
+/// Place characters between their last two fixed steps, depending on how far time has moved
+/// past the last one.
+fn interpolate_movement(
+    time: Res<Time<Fixed>>,
+    mut movement_query: Query<(&mut MovementController, &mut Transform)>,
+) {
+    for (mut controller, mut transform) in &mut movement_query {
+        let translation = transform.translation.xy();
+        if controller.rendered_position != Some(translation) {
+            // Something else moved the character, e.g. into another area, or it was just spawned.
+            controller.previous_position = translation;
+            controller.position = translation;
+        }
+        let position = controller
+            .previous_position
+            .lerp(controller.position, time.overstep_fraction());
+        transform.translation = position.extend(transform.translation.z);
+        controller.rendered_position = Some(position);
     }
 }
 
+/// Walk through an edge of the area into the area its [`Exit`] leads to, once a transition
+/// covered the screen.
 fn change_level(
-    window_query: Query<&Window, With<PrimaryWindow>>,
-    mut player_query: Query<&mut Transform, With<Player>>,
-    mut next_state: ResMut<NextState<Area>>,
+    player_query: Query<&MovementController, With<Player>>,
+    area: Res<State<Area>>,
+    areas: Areas,
+    settings: Res<TransitionSettings>,
+    mut transition: ResMut<Transition>,
 ) {
-    let Ok(window) = window_query.get_single() else {
+    let Some(definition) = areas.get(*area.get()) else {
         return;
     };
-    let half_width = window.size().x / 2.0 + 50.0;
-    for mut transform in &mut player_query {
-        if transform.translation.x > half_width {
-            transform.translation.x = -half_width;
-            next_state.set(Area::Outside);
-        } else if transform.translation.x < -half_width {
-            transform.translation.x = half_width;
-            next_state.set(Area::Cave);
+    let half_width = definition.width / 2.0 + 50.0;
+    let exits = definition.exits;
+    for controller in &player_query {
+        let exit = if controller.position.x > half_width {
+            exits.right
+        } else if controller.position.x < -half_width {
+            exits.left
+        } else {
+            None
+        };
+        let Some(Exit { to, x }) = exit else {
+            continue;
+        };
+        if transition.is_running() {
+            continue;
         }
+        transition.start(settings.area_style, move |world: &mut World| {
+            world.resource_mut::<NextState<Area>>().set(to);
+            let mut players =
+                world.query_filtered::<(&mut MovementController, &mut Transform), With<Player>>();
+            for (mut controller, mut transform) in players.iter_mut(world) {
+                controller.previous_position.x = x;
+                controller.position.x = x;
+                transform.translation.x = x;
+            }
+        });
     }
 }
 
+/// Keep the player from walking through edges of the area without an [`Exit`].
 fn clamp_player_x(
-    window_query: Query<&Window, With<PrimaryWindow>>,
-    mut player_query: Query<&mut Transform, With<Player>>,
+    mut player_query: Query<&mut MovementController, With<Player>>,
     area: Res<State<Area>>,
+    areas: Areas,
 ) {
-    let Ok(window) = window_query.get_single() else {
+    let Some(definition) = areas.get(*area.get()) else {
         return;
     };
-    let half_width = window.size().x / 2.0 - 50.0;
-    for mut transform in &mut player_query {
-        if transform.translation.x < -half_width && *area == Area::Cave {
-            transform.translation.x = -half_width;
-        } else if transform.translation.x > half_width && *area == Area::Outside {
-            transform.translation.x = half_width;
+    let half_width = definition.width / 2.0 - 50.0;
+    let exits = definition.exits;
+    for mut controller in &mut player_query {
+        let x = controller.position.x;
+        if (x < -half_width && exits.left.is_none()) || (x > half_width && exits.right.is_none()) {
+            controller.position.x = x.clamp(-half_width, half_width);
+            controller.velocity.x = 0.0;
         }
     }
 }
@@ -116,6 +306,8 @@ fn clamp_player_x(
 #[reflect(Resource)]
 pub struct ActionsFrozen {
     freeze_count: usize,
+    /// Actions are also frozen while a transition covers the screen.
+    in_transition: bool,
 }
 
 impl ActionsFrozen {
@@ -126,6 +318,12 @@ impl ActionsFrozen {
         self.freeze_count -= 1;
     }
     pub fn is_frozen(&self) -> bool {
-        self.freeze_count > 0
+        self.freeze_count > 0 || self.in_transition
+    }
+    pub fn in_transition(&self) -> bool {
+        self.in_transition
+    }
+    pub fn set_in_transition(&mut self, in_transition: bool) {
+        self.in_transition = in_transition;
     }
 }
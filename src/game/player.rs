@@ -2,8 +2,6 @@
 //! Note that this is separate from the `movement` module as that could be used
 //! for other characters as well.
 
-use std::time::Duration;
-
 use bevy::{
     prelude::*,
     render::texture::{ImageLoaderSettings, ImageSampler},
@@ -12,12 +10,15 @@ use bevy::{
 use crate::{
     asset_tracking::LoadResource,
     game::{animation::Animation, movement::MovementController},
+    input::{Action, Actions},
     screens::Screen,
     AppSet,
 };
 
 use super::{
-    animation::{AnimationData, AnimationState},
+    animation::AnimationSheet,
+    camera::Parallax,
+    health::{health_bar_sprite, Health, HealthBar},
     movement::ActionsFrozen,
 };
 
@@ -39,33 +40,7 @@ pub(super) fn plugin(app: &mut App) {
 #[reflect(Component)]
 pub struct Player;
 
-fn spawn_player(
-    mut commands: Commands,
-    player_assets: Res<PlayerAssets>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-) {
-    let layout = TextureAtlasLayout::from_grid(
-        UVec2::new(16, 23),
-        4,
-        1,
-        Some(UVec2::splat(2)),
-        Some(UVec2::splat(1)),
-    );
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    let idle = AnimationData {
-        frames: 2,
-        interval: Duration::from_millis(200),
-        state: AnimationState::Idling,
-        atlas_index: 0,
-    };
-    let walk = AnimationData {
-        frames: 2,
-        interval: Duration::from_millis(100),
-        state: AnimationState::Walking,
-        atlas_index: 2,
-    };
-    let player_animation = Animation::new(vec![idle, walk]);
-
+fn spawn_player(mut commands: Commands, player_assets: Res<PlayerAssets>) {
     commands.spawn((
         Name::new("Player"),
         Player,
@@ -75,32 +50,39 @@ fn spawn_player(
                 .with_translation(Vec3::new(-330.0, -70.0, 0.0)),
             ..Default::default()
         },
-        TextureAtlas {
-            layout: texture_atlas_layout.clone(),
-            index: player_animation.get_atlas_index(),
-        },
+        // The layout comes from the animation sheet.
+        TextureAtlas::default(),
         MovementController {
             max_speed: 300.0,
+            // The caveman is heavy, it takes him a moment to get going and to stop.
+            acceleration: 1500.0,
+            deceleration: 2000.0,
             ..default()
         },
-        player_animation,
+        Animation::new(player_assets.caveman_animation.clone()),
+        Health::new(10.0),
         StateScoped(Screen::Gameplay),
     ));
 
     commands.spawn((
         Name::new("Healthbar"),
+        HealthBar,
         SpriteBundle {
+            sprite: health_bar_sprite(1.0),
             texture: player_assets.healthbar.clone(),
             transform: Transform::from_scale(Vec2::splat(4.0).extend(1.0))
-                .with_translation(Vec3::new(-515.0, -320.0, 60.0)),
+                .with_translation(Vec3::new(-623.0, -320.0, 60.0)),
             ..Default::default()
         },
+        Parallax::fixed(Vec2::new(-623.0, -320.0)),
         StateScoped(Screen::Gameplay),
     ));
 }
 
 fn record_player_directional_input(
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
+    gamepads: Res<Gamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
     mut controllers: Query<&mut MovementController, With<Player>>,
     actions_frozen: Res<ActionsFrozen>,
 ) {
@@ -112,21 +94,32 @@ fn record_player_directional_input(
     }
     // Collect directional input.
     let mut intent = Vec2::ZERO;
-    if input.pressed(KeyCode::KeyA) || input.pressed(KeyCode::ArrowLeft) {
+    if actions.pressed(Action::MoveLeft) {
         intent.x -= 1.0;
     }
-    if input.pressed(KeyCode::KeyD) || input.pressed(KeyCode::ArrowRight) {
+    if actions.pressed(Action::MoveRight) {
         intent.x += 1.0;
     }
 
     // Normalize so that diagonal movement has the same speed as
     // horizontal and vertical movement.
-    // This should be omitted if the input comes from an analog stick instead.
-    let intent = intent.normalize_or_zero();
+    let mut intent = intent.normalize_or_zero();
+
+    // Analog sticks are not normalized, so that tilting them slightly walks slowly.
+    for gamepad in gamepads.iter() {
+        if let Some(x) = gamepad_axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)) {
+            intent.x += x;
+        }
+    }
+    let intent = intent.clamp_length_max(1.0);
+
+    let jump = actions.just_pressed(Action::Jump);
 
     // Apply movement intent to controllers.
     for mut controller in &mut controllers {
         controller.intent = intent;
+        // Keep the jump until a fixed step picks it up.
+        controller.jump |= jump;
     }
 }
 
@@ -135,40 +128,21 @@ pub struct PlayerAssets {
     #[dependency]
     pub caveman: Handle<Image>,
     #[dependency]
+    pub caveman_animation: Handle<AnimationSheet>,
+    #[dependency]
     pub healthbar: Handle<Image>,
     #[dependency]
     pub paper_big: Handle<Image>,
 
-    #[dependency]
-    pub item_pickup: Handle<AudioSource>,
-    #[dependency]
-    pub vine_boom: Handle<AudioSource>,
-    #[dependency]
-    pub uh_oh: Handle<AudioSource>,
-    #[dependency]
-    pub trophy_wife: Handle<AudioSource>,
-    #[dependency]
-    pub wife_hm: Handle<AudioSource>,
-    #[dependency]
-    pub run_outside: Handle<AudioSource>,
-    #[dependency]
-    pub run_cave: Handle<AudioSource>,
-
     #[dependency]
     pub animal_font: Handle<Font>,
 }
 
 impl PlayerAssets {
     pub const PATH_CAVEMAN: &'static str = "images/caveman.png";
+    pub const PATH_CAVEMAN_ANIMATION: &'static str = "data/caveman.anim.ron";
     pub const PATH_HEALTHBAR: &'static str = "images/health_bar.png";
     pub const PATH_PAPER_BIG: &'static str = "images/paper_big.png";
-    pub const PATH_ITEM_PICKUP: &'static str = "audio/sound_effects/item_pickup.ogg";
-    pub const PATH_VINE_BOOM: &'static str = "audio/sound_effects/vine_boom.ogg";
-    pub const PATH_UH_OH: &'static str = "audio/sound_effects/uh_oh.ogg";
-    pub const PATH_TROPHY_WIFE: &'static str = "audio/sound_effects/trophy_wife.ogg";
-    pub const PATH_WIFE_HM: &'static str = "audio/sound_effects/wife_hm.ogg";
-    pub const PATH_RUN_OUTSIDE: &'static str = "audio/sound_effects/run_outside.ogg";
-    pub const PATH_RUN_CAVE: &'static str = "audio/sound_effects/run_cave.ogg";
     pub const PATH_ANIMAL_FONT: &'static str = "fonts/Animal-Alphabet-Regular.ttf";
 }
 
@@ -182,6 +156,7 @@ impl FromWorld for PlayerAssets {
                     settings.sampler = ImageSampler::nearest();
                 },
             ),
+            caveman_animation: assets.load(PlayerAssets::PATH_CAVEMAN_ANIMATION),
             healthbar: assets.load_with_settings(
                 PlayerAssets::PATH_HEALTHBAR,
                 |settings: &mut ImageLoaderSettings| {
@@ -194,14 +169,6 @@ impl FromWorld for PlayerAssets {
                     settings.sampler = ImageSampler::nearest();
                 },
             ),
-            item_pickup: assets.load(PlayerAssets::PATH_ITEM_PICKUP),
-            vine_boom: assets.load(PlayerAssets::PATH_VINE_BOOM),
-            uh_oh: assets.load(PlayerAssets::PATH_UH_OH),
-            trophy_wife: assets.load(PlayerAssets::PATH_TROPHY_WIFE),
-            wife_hm: assets.load(PlayerAssets::PATH_WIFE_HM),
-
-            run_outside: assets.load(PlayerAssets::PATH_RUN_OUTSIDE),
-            run_cave: assets.load(PlayerAssets::PATH_RUN_CAVE),
             animal_font: assets.load(PlayerAssets::PATH_ANIMAL_FONT),
         }
     }
@@ -0,0 +1,112 @@
+//! Show which button interacts with the [`InteractionTarget`] right above it.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_tweening::{lens::TextColorLens, Animator, EaseFunction, Tween};
+
+use crate::{
+    input::{key_name, Action, InputDevice, Keymap},
+    screens::Screen,
+    AppSet,
+};
+
+use super::interaction::{Interactable, InteractionTarget};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_prompt);
+    app.add_systems(
+        Update,
+        (
+            update_prompt.run_if(
+                resource_changed::<InteractionTarget>
+                    .or_else(resource_changed::<InputDevice>)
+                    .or_else(resource_changed::<Keymap>),
+            ),
+            follow_target,
+        )
+            .chain()
+            .after(AppSet::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Component, Debug)]
+struct InteractionPrompt;
+
+const PROMPT_COLOR: Color = Color::srgb(0.925, 0.925, 0.925);
+const PROMPT_FADE: Duration = Duration::from_millis(150);
+/// Space between the top of the target and the prompt.
+const PROMPT_MARGIN: f32 = 24.0;
+
+fn spawn_prompt(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Interaction Prompt"),
+        InteractionPrompt,
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 28.0,
+                    color: PROMPT_COLOR.with_alpha(0.0),
+                    ..default()
+                },
+            ),
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 90.0)),
+            ..default()
+        },
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn update_prompt(
+    mut commands: Commands,
+    target: Res<InteractionTarget>,
+    device: Res<InputDevice>,
+    keymap: Res<Keymap>,
+    interactables: Query<&Interactable>,
+    mut prompt: Query<(Entity, &mut Text), With<InteractionPrompt>>,
+) {
+    let Ok((entity, mut text)) = prompt.get_single_mut() else {
+        return;
+    };
+    let interactable = target.0.and_then(|target| interactables.get(target).ok());
+    if let Some(interactable) = interactable {
+        let glyph = match *device {
+            InputDevice::Keyboard => keymap.keys(Action::Interact).first().copied().map(key_name),
+            InputDevice::Gamepad => Action::Interact.gamepad_glyph().map(str::to_string),
+        };
+        text.sections[0].value = match glyph {
+            Some(glyph) => format!("[{glyph}] {}", interactable.prompt),
+            None => interactable.prompt.clone(),
+        };
+    }
+
+    let start = text.sections[0].style.color;
+    let alpha = if interactable.is_some() { 1.0 } else { 0.0 };
+    commands.entity(entity).insert(Animator::new(Tween::new(
+        EaseFunction::QuadraticOut,
+        PROMPT_FADE,
+        TextColorLens {
+            start,
+            end: PROMPT_COLOR.with_alpha(alpha),
+            section: 0,
+        },
+    )));
+}
+
+fn follow_target(
+    target: Res<InteractionTarget>,
+    targets: Query<(&Aabb, &Transform), Without<InteractionPrompt>>,
+    mut prompt: Query<&mut Transform, With<InteractionPrompt>>,
+) {
+    let Some((aabb, target_transform)) = target.0.and_then(|target| targets.get(target).ok())
+    else {
+        return;
+    };
+    let top = aabb.half_extents.y * target_transform.scale.y;
+    for mut transform in &mut prompt {
+        transform.translation.x = target_transform.translation.x;
+        transform.translation.y = target_transform.translation.y + top + PROMPT_MARGIN;
+    }
+}
@@ -0,0 +1,189 @@
+//! Item conversions like cutting papyrus or burning a banana.
+//! Recipes are loaded from a data file and applied by triggering [`ApplyRecipe`].
+
+use bevy::prelude::*;
+use bevy_yarnspinner::prelude::DialogueRunner;
+use serde::Deserialize;
+
+use crate::{
+    asset_tracking::{LoadResource, LoadRonAsset},
+    audio::sound_bank::PlaySound,
+};
+
+use super::{
+    inventory::{Inventory, Item},
+    level::Level,
+    movement::ActionsFrozen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_ron_asset::<Recipes>("recipes.ron");
+    app.load_resource::<RecipeAssets>();
+    app.observe(apply_recipe);
+}
+
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct Recipes {
+    pub recipes: Vec<Recipe>,
+}
+
+impl Recipes {
+    pub fn get(&self, name: &str) -> Option<&Recipe> {
+        self.recipes.iter().find(|recipe| recipe.name == name)
+    }
+
+    /// The first recipe used at `station` that can be applied right now.
+    pub fn find_at_station(
+        &self,
+        station: Station,
+        inventory: &Inventory,
+        level: &Level,
+    ) -> Option<&Recipe> {
+        self.recipes
+            .iter()
+            .filter(|recipe| recipe.station == Some(station))
+            .find(|recipe| recipe.can_apply(inventory, level))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Recipe {
+    pub name: String,
+    /// Items that are consumed by this recipe.
+    pub inputs: Vec<Ingredient>,
+    /// Where this recipe is used, if it needs anything besides the inputs.
+    #[serde(default)]
+    pub station: Option<Station>,
+    /// Items that are created by this recipe.
+    pub outputs: Vec<Ingredient>,
+    /// Name of the sound that is played once the recipe is applied.
+    #[serde(default)]
+    pub sound: Option<String>,
+    /// Yarn node that is started once the recipe is applied, unless a dialogue is already running.
+    #[serde(default)]
+    pub node: Option<String>,
+}
+
+impl Recipe {
+    pub fn can_apply(&self, inventory: &Inventory, level: &Level) -> bool {
+        self.inputs.iter().all(|input| match input.location {
+            ItemLocation::Inventory => inventory.items.contains(&input.item),
+            ItemLocation::Level => level.items.contains(&input.item),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct Ingredient {
+    pub item: Item,
+    #[serde(default)]
+    pub location: ItemLocation,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ItemLocation {
+    #[default]
+    Inventory,
+    Level,
+}
+
+/// Something in the world that recipes can be used at.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Station {
+    Fire,
+    Wife,
+}
+
+/// Convert the inputs of the recipe with this name into its outputs.
+#[derive(Event, Debug)]
+pub struct ApplyRecipe {
+    pub name: String,
+}
+
+impl ApplyRecipe {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+fn apply_recipe(
+    trigger: Trigger<ApplyRecipe>,
+    mut commands: Commands,
+    recipe_assets: Res<RecipeAssets>,
+    recipes: Res<Assets<Recipes>>,
+    mut inventory: ResMut<Inventory>,
+    mut level: ResMut<Level>,
+    mut dialogue_runner: Query<&mut DialogueRunner>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+) {
+    let name = &trigger.event().name;
+    let Some(recipe) = recipes
+        .get(&recipe_assets.recipes)
+        .and_then(|recipes| recipes.get(name))
+    else {
+        error!("unknown recipe {name}");
+        return;
+    };
+    if !recipe.can_apply(&inventory, &level) {
+        warn!("missing inputs for recipe {name}");
+        return;
+    }
+    let Ok(mut dialogue_runner) = dialogue_runner.get_single_mut() else {
+        error!("no dialogue runner, not applying recipe {name}");
+        return;
+    };
+
+    for input in &recipe.inputs {
+        let items = match input.location {
+            ItemLocation::Inventory => &mut inventory.items,
+            ItemLocation::Level => &mut level.items,
+        };
+        if let Some(index) = items.iter().position(|x| *x == input.item) {
+            items.remove(index);
+        }
+    }
+    for output in &recipe.outputs {
+        match output.location {
+            ItemLocation::Inventory => inventory.items.push(output.item),
+            ItemLocation::Level => level.items.push(output.item),
+        }
+    }
+
+    let vars = dialogue_runner.variable_storage_mut();
+    for ingredient in recipe.inputs.iter().chain(&recipe.outputs) {
+        let has_item = inventory.items.contains(&ingredient.item);
+        if let Err(err) = vars.set(format!("$_has_{}", ingredient.item), has_item.into()) {
+            error!("could not update $_has_{}: {err}", ingredient.item);
+        }
+    }
+
+    if let Some(sound) = &recipe.sound {
+        commands.trigger(PlaySound::new(sound));
+    }
+
+    if let Some(node) = &recipe.node {
+        if !dialogue_runner.is_running() {
+            dialogue_runner.start_node(node);
+            actions_frozen.freeze();
+        }
+    }
+}
+
+#[derive(Resource, Asset, Reflect, Clone)]
+pub struct RecipeAssets {
+    #[dependency]
+    pub recipes: Handle<Recipes>,
+}
+
+impl RecipeAssets {
+    pub const PATH_RECIPES: &'static str = "data/items.recipes.ron";
+}
+
+impl FromWorld for RecipeAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Self {
+            recipes: assets.load(RecipeAssets::PATH_RECIPES),
+        }
+    }
+}
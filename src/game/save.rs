@@ -0,0 +1,332 @@
+//! Save the progress of the current game and continue it in a later session.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_yarnspinner::{
+    events::DialogueCompleteEvent,
+    prelude::{DialogueRunner, YarnValue},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    screens::{Area, Difficulty, Screen},
+    storage,
+};
+
+use super::{
+    health::Health,
+    inventory::{Inventory, Item},
+    level::Level,
+    movement::ActionsFrozen,
+    player::Player,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SaveRequested>();
+    app.add_systems(
+        Update,
+        (
+            restore_save.run_if(resource_exists::<LoadedSave>),
+            request_save.run_if(
+                not(resource_exists::<LoadedSave>).and_then(
+                    resource_changed::<Inventory>
+                        .or_else(resource_changed::<Level>)
+                        .or_else(state_changed::<Area>)
+                        .or_else(on_event::<DialogueCompleteEvent>()),
+                ),
+            ),
+            save_progress.run_if(resource_equals(SaveRequested(true)).and_then(can_save)),
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnEnter(Screen::Gameplay), |mut commands: Commands| {
+        commands.insert_resource(SaveRequested::default())
+    });
+    app.add_systems(OnEnter(Screen::End), delete_save);
+}
+
+const SAVE_KEY: &str = "save";
+
+/// Saves from other versions are ignored instead of being migrated.
+const SAVE_VERSION: u32 = 1;
+
+/// Everything needed to continue a game where it was left off.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveData {
+    version: u32,
+    inventory: Vec<Item>,
+    level: Vec<Item>,
+    area: Area,
+    #[serde(default)]
+    difficulty: Difficulty,
+    player_translation: Vec3,
+    /// Saves from before health was saved continue with full health.
+    #[serde(default)]
+    player_health: Option<f32>,
+    variables: HashMap<String, SavedValue>,
+}
+
+impl SaveData {
+    /// Read the save of the last game, if there is a compatible one.
+    pub fn load() -> Option<Self> {
+        let save = Self::from_ron(&storage::read(SAVE_KEY)?)
+            .map_err(|err| warn!("could not read save: {err}"))
+            .ok()?;
+        if save.version != SAVE_VERSION {
+            warn!("ignoring save from version {}", save.version);
+            return None;
+        }
+        Some(save)
+    }
+
+    fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+
+    fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, default()).expect("save data is serializable")
+    }
+}
+
+/// A yarn variable as it is stored in a save.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum SavedValue {
+    Number(f32),
+    String(String),
+    Boolean(bool),
+}
+
+impl From<YarnValue> for SavedValue {
+    fn from(value: YarnValue) -> Self {
+        match value {
+            YarnValue::Number(number) => SavedValue::Number(number),
+            YarnValue::String(string) => SavedValue::String(string),
+            YarnValue::Boolean(boolean) => SavedValue::Boolean(boolean),
+        }
+    }
+}
+
+impl From<SavedValue> for YarnValue {
+    fn from(value: SavedValue) -> Self {
+        match value {
+            SavedValue::Number(number) => YarnValue::Number(number),
+            SavedValue::String(string) => YarnValue::String(string),
+            SavedValue::Boolean(boolean) => YarnValue::Boolean(boolean),
+        }
+    }
+}
+
+/// Inserting this before entering [`Screen::Gameplay`] continues the game from the save
+/// instead of starting a new one.
+#[derive(Resource, Debug)]
+pub struct LoadedSave(pub SaveData);
+
+/// Progress changed and waits to be saved.
+#[derive(Resource, Debug, Default, PartialEq)]
+struct SaveRequested(bool);
+
+fn request_save(mut requested: ResMut<SaveRequested>) {
+    requested.0 = true;
+}
+
+/// Cutscenes change the progress before they play out, so saving in the middle of one would
+/// continue the game after it without ever playing the rest.
+fn can_save(dialogue_runner: Query<&DialogueRunner>, actions_frozen: Res<ActionsFrozen>) -> bool {
+    !dialogue_runner.iter().any(DialogueRunner::is_running) && !actions_frozen.is_frozen()
+}
+
+fn save_progress(world: &mut World) {
+    world.resource_mut::<SaveRequested>().0 = false;
+    let save = capture(world);
+    storage::write(SAVE_KEY, &save.to_ron());
+}
+
+fn restore_save(world: &mut World) {
+    let Some(LoadedSave(save)) = world.remove_resource::<LoadedSave>() else {
+        return;
+    };
+    restore(world, save);
+}
+
+fn delete_save() {
+    storage::remove(SAVE_KEY);
+}
+
+fn capture(world: &mut World) -> SaveData {
+    let player_translation = world
+        .query_filtered::<&Transform, With<Player>>()
+        .get_single(world)
+        .map(|transform| transform.translation)
+        .unwrap_or_default();
+    let player_health = world
+        .query_filtered::<&Health, With<Player>>()
+        .get_single(world)
+        .map(|health| health.current)
+        .ok();
+    let variables = world
+        .query::<&DialogueRunner>()
+        .get_single(world)
+        .map(|runner| runner.variable_storage().variables())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, value)| (name, value.into()))
+        .collect();
+
+    SaveData {
+        version: SAVE_VERSION,
+        inventory: world.resource::<Inventory>().items.clone(),
+        level: world.resource::<Level>().items.clone(),
+        area: *world.resource::<State<Area>>().get(),
+        difficulty: *world.resource::<Difficulty>(),
+        player_translation,
+        player_health,
+        variables,
+    }
+}
+
+fn restore(world: &mut World, save: SaveData) {
+    world.resource_mut::<Inventory>().items = save.inventory;
+    world.resource_mut::<Level>().items = save.level;
+    world.resource_mut::<NextState<Area>>().set(save.area);
+    world.insert_resource(save.difficulty);
+    world.insert_resource(ActionsFrozen::default());
+
+    for mut transform in world
+        .query_filtered::<&mut Transform, With<Player>>()
+        .iter_mut(world)
+    {
+        transform.translation = save.player_translation;
+    }
+    if let Some(current) = save.player_health {
+        for mut health in world
+            .query_filtered::<&mut Health, With<Player>>()
+            .iter_mut(world)
+        {
+            health.current = current.min(health.max);
+        }
+    }
+
+    for mut runner in world.query::<&mut DialogueRunner>().iter_mut(world) {
+        let vars = runner.variable_storage_mut();
+        for (name, value) in &save.variables {
+            if let Err(err) = vars.set(name.clone(), value.clone().into()) {
+                error!("could not restore {name}: {err}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_yarnspinner::prelude::YarnProject;
+
+    use super::*;
+    use crate::test_support::yarn_app;
+
+    fn gameplay_app() -> App {
+        let mut app = yarn_app("title: Start\n---\nHello.\n===\n");
+        app.init_state::<Screen>();
+        app.add_sub_state::<Area>();
+        app.init_resource::<Inventory>();
+        app.init_resource::<Level>();
+        app.init_resource::<ActionsFrozen>();
+        app.init_resource::<Difficulty>();
+        app.world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Gameplay);
+        app.update();
+        let runner = app
+            .world()
+            .resource::<YarnProject>()
+            .create_dialogue_runner();
+        app.world_mut().spawn(runner);
+        app.world_mut().spawn((
+            Player,
+            Transform::from_translation(Vec3::new(-330.0, -70.0, 0.0)),
+            Health::new(10.0),
+        ));
+        app
+    }
+
+    fn set_variable(world: &mut World, name: &str, value: YarnValue) {
+        let mut runner = world.query::<&mut DialogueRunner>();
+        runner
+            .single_mut(world)
+            .variable_storage_mut()
+            .set(name.to_string(), value)
+            .unwrap();
+    }
+
+    fn variable(world: &mut World, name: &str) -> Option<YarnValue> {
+        let mut runner = world.query::<&DialogueRunner>();
+        runner.single(world).variable_storage().get(name).ok()
+    }
+
+    #[test]
+    fn save_round_trip_mid_puzzle() {
+        let mut app = gameplay_app();
+        // The papyrus has been cut and the player walked into the cave to see the wife.
+        app.world_mut().resource_mut::<Inventory>().items = vec![Item::Knife, Item::PapyrusStrips];
+        app.world_mut().resource_mut::<Level>().items = vec![Item::Banana];
+        app.world_mut()
+            .resource_mut::<NextState<Area>>()
+            .set(Area::Cave);
+        let world = app.world_mut();
+        let mut player = world.query_filtered::<(&mut Transform, &mut Health), With<Player>>();
+        let (mut transform, mut health) = player.single_mut(world);
+        transform.translation = Vec3::new(120.0, -70.0, 0.0);
+        health.current = 6.0;
+        set_variable(world, "$learned_pen", true.into());
+        set_variable(world, "$_has_Knife", true.into());
+        set_variable(world, "$_has_Papyrus", false.into());
+        set_variable(world, "$difficulty", "Brutal".into());
+        app.update();
+
+        let save = capture(app.world_mut());
+        let loaded = SaveData::from_ron(&save.to_ron()).unwrap();
+        assert_eq!(loaded, save);
+
+        let mut restored = gameplay_app();
+        restore(restored.world_mut(), loaded);
+        restored.update();
+
+        let world = restored.world_mut();
+        assert_eq!(
+            world.resource::<Inventory>().items,
+            [Item::Knife, Item::PapyrusStrips]
+        );
+        assert_eq!(world.resource::<Level>().items, [Item::Banana]);
+        assert_eq!(*world.resource::<State<Area>>().get(), Area::Cave);
+        let translation = world
+            .query_filtered::<&Transform, With<Player>>()
+            .single(world)
+            .translation;
+        assert_eq!(translation, Vec3::new(120.0, -70.0, 0.0));
+        let health = world
+            .query_filtered::<&Health, With<Player>>()
+            .single(world)
+            .current;
+        assert_eq!(health, 6.0);
+        assert_eq!(variable(world, "$learned_pen"), Some(true.into()));
+        assert_eq!(variable(world, "$_has_Knife"), Some(true.into()));
+        assert_eq!(variable(world, "$_has_Papyrus"), Some(false.into()));
+        assert_eq!(variable(world, "$difficulty"), Some("Brutal".into()));
+        assert_eq!(capture(world), save);
+    }
+
+    #[test]
+    fn yarn_values_round_trip() {
+        for value in [
+            YarnValue::Boolean(true),
+            YarnValue::Number(4.5),
+            YarnValue::String("Brutal".into()),
+        ] {
+            let saved = SavedValue::from(value.clone());
+            let text = ron::to_string(&saved).unwrap();
+            let loaded: SavedValue = ron::from_str(&text).unwrap();
+            assert_eq!(YarnValue::from(loaded), value);
+        }
+    }
+}
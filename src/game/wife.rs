@@ -1,127 +1,64 @@
-use std::time::Duration;
-
 use bevy::{
-    math::bounding::{Aabb2d, IntersectsVolume},
     prelude::*,
-    render::{
-        primitives::Aabb,
-        texture::{ImageLoaderSettings, ImageSampler},
-    },
+    render::texture::{ImageLoaderSettings, ImageSampler},
 };
-use bevy_yarnspinner::prelude::DialogueRunner;
 
 use super::{
-    animation::{Animation, AnimationData, AnimationState},
-    movement::ActionsFrozen,
-    player::Player,
-};
-use crate::{
-    asset_tracking::LoadResource,
-    screens::{Area, Screen},
+    animation::{Animation, AnimationSheet},
+    area::{Resident, SpawnResident},
+    interaction::{InteractAction, Interactable},
 };
+use crate::asset_tracking::LoadResource;
 
 pub(super) fn plugin(app: &mut App) {
     app.load_resource::<WifeAssets>();
-
-    app.add_systems(Update, talk.run_if(in_state(Screen::Gameplay)));
-    app.observe(start_wife_dialogue);
+    app.observe(spawn_wife);
 }
 
 #[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Wife;
 
-pub fn spawn_wife(
+fn spawn_wife(
+    trigger: Trigger<SpawnResident>,
     mut commands: Commands,
     player_assets: Res<WifeAssets>,
-    mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
-    let layout = TextureAtlasLayout::from_grid(
-        UVec2::new(15, 21),
-        4,
-        1,
-        Some(UVec2::splat(2)),
-        Some(UVec2::splat(1)),
-    );
-    let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    let idle = AnimationData {
-        frames: 2,
-        interval: Duration::from_millis(200),
-        state: AnimationState::Idling,
-        atlas_index: 0,
-    };
-    let player_animation = Animation::new(vec![idle]);
-
+    let event = trigger.event();
+    if event.resident != Resident::Wife {
+        return;
+    }
     commands.spawn((
         Name::new("Wife"),
         Wife,
         SpriteBundle {
             texture: player_assets.wife.clone(),
             transform: Transform::from_scale(Vec2::splat(8.0).extend(1.0))
-                .with_translation(Vec3::new(-400.0, -78.0, 0.0)),
+                .with_translation(event.translation),
             ..Default::default()
         },
-        TextureAtlas {
-            layout: texture_atlas_layout.clone(),
-            index: player_animation.get_atlas_index(),
+        // The layout comes from the animation sheet.
+        TextureAtlas::default(),
+        Animation::new(player_assets.wife_animation.clone()),
+        Interactable {
+            priority: 1,
+            prompt: "Talk".to_string(),
+            action: InteractAction::StartNode("Wife".to_string()),
         },
-        player_animation,
-        StateScoped(Area::Cave),
+        StateScoped(event.area),
     ));
 }
 
-fn talk(
-    mut commands: Commands,
-    input: Res<ButtonInput<KeyCode>>,
-    player: Query<(&Aabb, &Transform), With<Player>>,
-    wife: Query<(&Aabb, &Transform), With<Wife>>,
-    actions_frozen: Res<ActionsFrozen>,
-) {
-    if actions_frozen.is_frozen() {
-        return;
-    }
-    for (player_aabb, player_transform) in &player {
-        let player_aabb2d = Aabb2d::new(
-            player_transform.translation.xy(),
-            player_aabb.half_extents.xy() * player_transform.scale.xy(),
-        );
-        for (item_aabb, item_transform) in &wife {
-            let item_aabb2d = Aabb2d::new(
-                item_transform.translation.xy(),
-                item_aabb.half_extents.xy() * item_transform.scale.xy(),
-            );
-            if player_aabb2d.intersects(&item_aabb2d) {
-                if !input.just_pressed(KeyCode::KeyE) {
-                    return;
-                }
-                commands.trigger(DialogueStart);
-            }
-        }
-    }
-}
-
-#[derive(Debug, Event)]
-struct DialogueStart;
-
-fn start_wife_dialogue(
-    _trigger: Trigger<DialogueStart>,
-    mut actions_frozen: ResMut<ActionsFrozen>,
-    mut dialogue_runner: Query<&mut DialogueRunner>,
-) {
-    let mut dialogue_runner = dialogue_runner
-        .get_single_mut()
-        .expect("only one dialogue runner");
-    dialogue_runner.start_node("Wife");
-    actions_frozen.freeze();
-}
-
 #[derive(Resource, Asset, Reflect, Clone)]
 pub struct WifeAssets {
     #[dependency]
     pub wife: Handle<Image>,
+    #[dependency]
+    pub wife_animation: Handle<AnimationSheet>,
 }
 
 impl WifeAssets {
     pub const PATH_WIFE: &'static str = "images/wife.png";
+    pub const PATH_WIFE_ANIMATION: &'static str = "data/wife.anim.ron";
 }
 
 impl FromWorld for WifeAssets {
@@ -134,6 +71,7 @@ impl FromWorld for WifeAssets {
                     settings.sampler = ImageSampler::nearest();
                 },
             ),
+            wife_animation: assets.load(WifeAssets::PATH_WIFE_ANIMATION),
         }
     }
 }
@@ -0,0 +1,263 @@
+//! Keep track of how the player is currently playing the game, and which keys do what.
+//! Game code asks for [`Action`]s instead of literal keys, so players can rebind them in the
+//! controls screen. Their [`Keymap`] is persisted between runs.
+
+use std::collections::HashMap;
+
+use bevy::{ecs::system::SystemParam, input::InputSystem, prelude::*};
+use derive_more::derive::Display;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<InputDevice>();
+    app.insert_resource(Keymap::load());
+    app.add_systems(PreUpdate, detect_input_device.after(InputSystem));
+}
+
+/// The device the player used last, e.g. to show matching button prompts.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+fn detect_input_device(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut device: ResMut<InputDevice>,
+) {
+    let used = if gamepad_buttons.get_just_pressed().next().is_some() {
+        InputDevice::Gamepad
+    } else if keys.get_just_pressed().next().is_some() || mouse.get_just_pressed().next().is_some()
+    {
+        InputDevice::Keyboard
+    } else {
+        return;
+    };
+    if *device != used {
+        *device = used;
+    }
+}
+
+/// Something the player can do, independent of the key it is bound to.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    #[display("Move left")]
+    MoveLeft,
+    #[display("Move right")]
+    MoveRight,
+    #[display("Jump")]
+    Jump,
+    #[display("Interact")]
+    Interact,
+    /// Close the paper or skip the splash screen.
+    #[display("Cancel")]
+    Cancel,
+    #[display("Toggle inspector")]
+    ToggleInspector,
+    #[display("Toggle UI debug")]
+    ToggleUiDebug,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Interact,
+        Action::Cancel,
+        Action::ToggleInspector,
+        Action::ToggleUiDebug,
+    ];
+
+    /// Whether the action only exists in dev builds.
+    pub fn is_dev(self) -> bool {
+        matches!(self, Action::ToggleInspector | Action::ToggleUiDebug)
+    }
+
+    fn default_keys(self) -> Vec<KeyCode> {
+        match self {
+            Action::MoveLeft => vec![KeyCode::KeyA, KeyCode::ArrowLeft],
+            Action::MoveRight => vec![KeyCode::KeyD, KeyCode::ArrowRight],
+            Action::Jump => vec![KeyCode::Space, KeyCode::KeyW, KeyCode::ArrowUp],
+            Action::Interact => vec![KeyCode::KeyE],
+            Action::Cancel => vec![KeyCode::Escape],
+            Action::ToggleInspector => vec![KeyCode::Backquote],
+            Action::ToggleUiDebug => vec![KeyCode::KeyU],
+        }
+    }
+
+    /// Gamepad buttons can't be rebound.
+    fn gamepad_button(self) -> Option<GamepadButtonType> {
+        match self {
+            Action::MoveLeft => Some(GamepadButtonType::DPadLeft),
+            Action::MoveRight => Some(GamepadButtonType::DPadRight),
+            Action::Jump => Some(GamepadButtonType::North),
+            Action::Interact => Some(GamepadButtonType::South),
+            Action::Cancel => Some(GamepadButtonType::East),
+            Action::ToggleInspector | Action::ToggleUiDebug => None,
+        }
+    }
+
+    /// The name of the gamepad button that triggers this action, for prompts.
+    pub fn gamepad_glyph(self) -> Option<&'static str> {
+        self.gamepad_button().map(|button_type| match button_type {
+            GamepadButtonType::South => "A",
+            GamepadButtonType::East => "B",
+            GamepadButtonType::North => "Y",
+            GamepadButtonType::DPadLeft => "Left",
+            GamepadButtonType::DPadRight => "Right",
+            _ => "?",
+        })
+    }
+}
+
+const KEYMAP_KEY: &str = "keymap";
+
+/// The keys bound to each [`Action`].
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Read the bindings of the last run. Actions that were added since then get their defaults.
+    pub fn load() -> Self {
+        let mut keymap = Self::default();
+        let Some(text) = storage::read(KEYMAP_KEY) else {
+            return keymap;
+        };
+        match ron::from_str::<Keymap>(&text) {
+            Ok(saved) => keymap.bindings.extend(saved.bindings),
+            Err(err) => warn!("could not read keymap: {err}"),
+        }
+        keymap
+    }
+
+    pub fn save(&self) {
+        let text = ron::ser::to_string_pretty(self, default()).expect("keymap is serializable");
+        storage::write(KEYMAP_KEY, &text);
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The other action that `key` is already bound to, if any.
+    /// Dev actions don't count outside of dev builds, where nothing reads them.
+    pub fn conflict(&self, action: Action, key: KeyCode) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|other| *other != action)
+            .filter(|other| !other.is_dev() || cfg!(feature = "dev"))
+            .find(|other| self.keys(*other).contains(&key))
+    }
+
+    /// Replace the key in `slot` of `action`'s keys with `key`, keeping the others.
+    /// Slots past the bound keys add `key` to them.
+    pub fn rebind(&mut self, action: Action, slot: usize, key: KeyCode) {
+        let keys = self.bindings.entry(action).or_default();
+        match keys.get_mut(slot) {
+            Some(bound) => *bound = key,
+            None => keys.push(key),
+        }
+    }
+
+    /// How many keys can be bound to `action` in the controls screen: enough for its current and
+    /// default keys.
+    pub fn slots(&self, action: Action) -> usize {
+        self.keys(action).len().max(action.default_keys().len())
+    }
+}
+
+/// A short name of the key, e.g. "E" instead of "KeyE".
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    ["Key", "Digit", "Arrow"]
+        .into_iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .map_or_else(|| name.clone(), str::to_string)
+}
+
+/// Read [`Action`]s from the keyboard through the [`Keymap`] and from all connected gamepads.
+#[derive(SystemParam)]
+pub struct Actions<'w> {
+    keymap: Res<'w, Keymap>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+}
+
+impl Actions<'_> {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.keys
+            .any_pressed(self.keymap.keys(action).iter().copied())
+            || self.gamepad(action, |buttons, button| buttons.pressed(button))
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.keys
+            .any_just_pressed(self.keymap.keys(action).iter().copied())
+            || self.gamepad(action, |buttons, button| buttons.just_pressed(button))
+    }
+
+    fn gamepad(
+        &self,
+        action: Action,
+        check: impl Fn(&ButtonInput<GamepadButton>, GamepadButton) -> bool,
+    ) -> bool {
+        let Some(button_type) = action.gamepad_button() else {
+            return false;
+        };
+        self.gamepads.iter().any(|gamepad| {
+            check(
+                &self.gamepad_buttons,
+                GamepadButton::new(gamepad, button_type),
+            )
+        })
+    }
+}
+
+/// A run condition that is true when the action was just pressed.
+pub fn action_just_pressed(action: Action) -> impl FnMut(Actions) -> bool + Clone {
+    move |actions: Actions| actions.just_pressed(action)
+}
+
+/// A run condition that flips every time the action is pressed, starting out as `default`.
+pub fn action_toggle_active(
+    default: bool,
+    action: Action,
+) -> impl FnMut(Actions, Local<bool>) -> bool + Clone {
+    move |actions: Actions, mut toggled: Local<bool>| {
+        if actions.just_pressed(action) {
+            *toggled = !*toggled;
+        }
+        default != *toggled
+    }
+}
+
+/// A run condition that is true when any connected gamepad just pressed the button.
+pub fn gamepad_just_pressed(
+    button_type: GamepadButtonType,
+) -> impl FnMut(Res<Gamepads>, Res<ButtonInput<GamepadButton>>) -> bool + Clone {
+    move |gamepads: Res<Gamepads>, buttons: Res<ButtonInput<GamepadButton>>| {
+        gamepads
+            .iter()
+            .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    }
+}
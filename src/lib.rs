@@ -1,31 +1,33 @@
 mod asset_tracking;
 pub mod audio;
+mod canvas;
 #[cfg(feature = "dev")]
 mod dev_tools;
 mod dialogue;
 mod game;
+mod input;
+#[cfg(not(target_family = "wasm"))]
+mod replay;
 mod screens;
+mod storage;
+#[cfg(test)]
+mod test_support;
+#[cfg(test)]
+mod tests;
 mod theme;
+mod transition;
 
 use bevy::{
-    asset::AssetMetaCheck,
-    audio::{AudioPlugin, Volume},
-    prelude::*,
-    window::WindowResolution,
+    asset::AssetMetaCheck, prelude::*, render::camera::RenderTarget, window::WindowResolution,
 };
 use bevy_tweening::TweeningPlugin;
+use canvas::{Canvas, CANVAS_LAYER};
+use game::camera::CameraFollow;
 
 pub struct AppPlugin;
 
 impl Plugin for AppPlugin {
     fn build(&self, app: &mut App) {
-        app.configure_sets(
-            Update,
-            (AppSet::TickTimers, AppSet::RecordInput, AppSet::Update).chain(),
-        );
-
-        app.add_systems(Startup, spawn_camera);
-
         app.add_plugins(
             DefaultPlugins
                 .set(AssetPlugin {
@@ -41,34 +43,52 @@ impl Plugin for AppPlugin {
                         canvas: Some("#bevy".to_string()),
                         fit_canvas_to_parent: true,
                         prevent_default_event_handling: true,
-                        resizable: false,
+                        // The canvas is scaled by whole physical pixels to fit the window.
                         resolution: WindowResolution::default().with_scale_factor_override(1.0),
                         ..default()
                     }
                     .into(),
                     ..default()
-                })
-                .set(AudioPlugin {
-                    global_volume: GlobalVolume {
-                        volume: Volume::new(0.3),
-                    },
-                    ..default()
                 }),
         );
 
+        app.add_plugins((GamePlugin, dialogue::view_plugin));
+
+        // Enable dev tools for dev builds.
+        #[cfg(feature = "dev")]
+        app.add_plugins(dev_tools::plugin);
+    }
+}
+
+/// The game itself, without Bevy's plugins, the dialogue view and dev tools.
+/// Tests add it to a headless app.
+struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            Update,
+            (AppSet::TickTimers, AppSet::RecordInput, AppSet::Update).chain(),
+        );
+
+        app.add_systems(Startup, spawn_camera);
+
         app.add_plugins(TweeningPlugin);
 
         app.add_plugins((
             asset_tracking::plugin,
+            audio::plugin,
+            canvas::plugin,
             game::plugin,
             screens::plugin,
             theme::plugin,
             dialogue::plugin,
+            input::plugin,
+            transition::plugin,
         ));
 
-        // Enable dev tools for dev builds.
-        #[cfg(feature = "dev")]
-        app.add_plugins(dev_tools::plugin);
+        #[cfg(not(target_family = "wasm"))]
+        app.add_plugins(replay::plugin);
     }
 }
 
@@ -85,16 +105,38 @@ enum AppSet {
     Update,
 }
 
-fn spawn_camera(mut commands: Commands) {
+fn spawn_camera(mut commands: Commands, canvas: Res<Canvas>) {
+    commands.spawn((
+        Name::new("World Camera"),
+        Camera2dBundle {
+            camera: Camera {
+                // Render before the window camera shows the canvas.
+                order: -1,
+                target: RenderTarget::Image(canvas.image.clone()),
+                ..default()
+            },
+            ..default()
+        },
+        // Follows the player during gameplay.
+        CameraFollow::default(),
+    ));
     commands.spawn((
-        Name::new("Camera"),
-        Camera2dBundle::default(),
+        Name::new("Window Camera"),
+        Camera2dBundle {
+            camera: Camera {
+                // Letterbox the canvas.
+                clear_color: ClearColorConfig::Custom(Color::BLACK),
+                ..default()
+            },
+            ..default()
+        },
+        CANVAS_LAYER,
         // Render all UI to this camera.
-        // Not strictly necessary since we only use one camera,
-        // but if we don't use this component, our UI will disappear as soon
-        // as we add another camera. This includes indirect ways of adding cameras like using
+        // The UI can't be rendered to the canvas, as it wouldn't receive clicks there,
+        // and if we don't use this component, our UI will disappear as Bevy picks another camera.
+        // This includes indirect ways of adding cameras like using
         // [ui node outlines](https://bevyengine.org/news/bevy-0-14/#ui-node-outline-gizmos)
-        // for debugging. So it's good to have this here for future-proofing.
+        // for debugging.
         IsDefaultUiCamera,
     ));
 }
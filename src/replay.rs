@@ -0,0 +1,551 @@
+//! Record the input of a game and replay it exactly, e.g. to attach a replay to a bug report or to
+//! turn a real playthrough into a regression test.
+//!
+//! Start the game with `--record <file>` to write the next game to the file once it ends, and with
+//! `--replay <file>` to start the recorded game from the title screen on its own.
+//! Besides keyboard input, dialogue advances and used inventory items, a [`Recording`] holds the
+//! length of every frame, so timers, tweens and the fixed timestep of the movement play out the
+//! same, and the keymap and save the game was played with, so the keys do the same and a
+//! continued game starts where it did. Gamepads are not recorded, and the keyboard and mouse are
+//! ignored during a replay.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput, NativeKey},
+        mouse::MouseButtonInput,
+        ButtonState, InputSystem,
+    },
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use bevy_yarnspinner::{
+    events::{DialogueCompleteEvent, PresentLineEvent, PresentOptionsEvent},
+    prelude::{DialogueRunner, OptionId, YarnProject},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dialogue::OPTION_BUTTONS,
+    game::{
+        inventory::Item,
+        save::{LoadedSave, SaveData},
+    },
+    input::Keymap,
+    screens::{Difficulty, Screen},
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    if let Some(recorder) = Recorder::from_args() {
+        app.insert_resource(recorder);
+    }
+    if let Some(replayer) = Replayer::from_args() {
+        app.insert_resource(replayer);
+    }
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (
+            start_recording.run_if(resource_exists::<Recorder>),
+            start_replay_frames.run_if(resource_exists::<Replayer>),
+        ),
+    );
+    app.add_systems(
+        OnExit(Screen::Gameplay),
+        finish_recording.run_if(resource_exists::<Recorder>),
+    );
+    app.add_systems(
+        Update,
+        start_replay.run_if(
+            in_state(Screen::Title)
+                .and_then(resource_exists::<Replayer>)
+                .and_then(resource_exists::<YarnProject>),
+        ),
+    );
+
+    app.add_systems(
+        PreUpdate,
+        (
+            replay_keys.run_if(is_replaying).before(InputSystem),
+            replay_items.run_if(is_replaying).after(InputSystem),
+            record_keys.run_if(is_recording).after(InputSystem),
+        ),
+    );
+    app.add_systems(
+        PostUpdate,
+        (
+            replay_dialogue.run_if(is_replaying),
+            record_dialogue.run_if(is_recording),
+        )
+            .chain(),
+    );
+    app.add_systems(
+        Last,
+        (
+            record_frame.run_if(is_recording),
+            next_replay_frame.run_if(is_replaying),
+        ),
+    );
+    app.observe(record_item);
+}
+
+/// The input of one game, from entering gameplay until leaving it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    pub difficulty: Difficulty,
+    /// The keys that were bound when the game was played.
+    #[serde(default)]
+    pub keymap: Keymap,
+    /// The save the game was continued from, if it was.
+    #[serde(default)]
+    pub save: Option<SaveData>,
+    /// The length of every frame in nanoseconds.
+    pub frames: Vec<u64>,
+    /// The input of each frame, ordered by the index of the frame.
+    pub inputs: Vec<(usize, ReplayInput)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReplayInput {
+    /// A key was pressed or released. `text` is the character it typed, if any.
+    Key {
+        key: KeyCode,
+        text: Option<String>,
+        pressed: bool,
+    },
+    /// The dialogue continued after a line.
+    Continue,
+    /// The option with this index was chosen.
+    Choose(usize),
+    /// The item was pressed in the inventory.
+    UseItem(Item),
+}
+
+impl Recording {
+    fn new(difficulty: Difficulty, keymap: Keymap, save: Option<SaveData>) -> Self {
+        Self {
+            difficulty,
+            keymap,
+            save,
+            frames: Vec::new(),
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        ron::from_str(&text).map_err(|err| err.to_string())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let config = ron::ser::PrettyConfig::default().compact_arrays(true);
+        let text = ron::ser::to_string_pretty(self, config).expect("recording is serializable");
+        fs::write(path, text).map_err(|err| err.to_string())
+    }
+}
+
+/// The value following `flag` on the command line.
+fn path_arg(flag: &str) -> Option<PathBuf> {
+    let mut args = std::env::args();
+    args.find(|arg| arg == flag)?;
+    args.next().map(PathBuf::from)
+}
+
+/// Records every game while it exists.
+#[derive(Resource, Debug, Default)]
+pub struct Recorder {
+    /// Where the recording is written once the game ends.
+    pub path: Option<PathBuf>,
+    /// The game that is being recorded, or the last one once it ended.
+    pub recording: Option<Recording>,
+    active: bool,
+}
+
+impl Recorder {
+    fn from_args() -> Option<Self> {
+        path_arg("--record").map(|path| Self {
+            path: Some(path),
+            ..default()
+        })
+    }
+
+    fn push(&mut self, input: ReplayInput) {
+        if let Some(recording) = &mut self.recording {
+            let frame = recording.frames.len();
+            recording.inputs.push((frame, input));
+        }
+    }
+}
+
+/// Starts the recorded game from the title screen and feeds it the recorded input.
+/// Removes itself once the replay is over.
+#[derive(Resource, Debug)]
+pub struct Replayer {
+    recording: Recording,
+    /// The index of the current frame, once the game started.
+    frame: Option<usize>,
+    /// The player's own keymap, which is put back once the replay is over.
+    keymap: Option<Keymap>,
+}
+
+impl Replayer {
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            frame: None,
+            keymap: None,
+        }
+    }
+
+    fn from_args() -> Option<Self> {
+        let path = path_arg("--replay")?;
+        match Recording::load(&path) {
+            Ok(recording) => Some(Self::new(recording)),
+            Err(err) => {
+                error!("could not read replay {}: {err}", path.display());
+                None
+            }
+        }
+    }
+
+    /// The input of the current frame.
+    fn inputs(&self) -> impl Iterator<Item = &ReplayInput> {
+        let frame = self.frame.unwrap_or_default();
+        let inputs = &self.recording.inputs;
+        let start = inputs.partition_point(|(f, _)| *f < frame);
+        inputs[start..]
+            .iter()
+            .take_while(move |(f, _)| *f == frame)
+            .map(|(_, input)| input)
+    }
+}
+
+fn is_recording(recorder: Option<Res<Recorder>>) -> bool {
+    recorder.is_some_and(|recorder| recorder.active)
+}
+
+fn is_replaying(replayer: Option<Res<Replayer>>) -> bool {
+    replayer.is_some_and(|replayer| replayer.frame.is_some())
+}
+
+fn start_recording(
+    mut recorder: ResMut<Recorder>,
+    difficulty: Res<Difficulty>,
+    keymap: Res<Keymap>,
+    loaded_save: Option<Res<LoadedSave>>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
+    recorder.recording = Some(Recording::new(
+        *difficulty,
+        keymap.clone(),
+        loaded_save.map(|loaded_save| loaded_save.0.clone()),
+    ));
+    recorder.active = true;
+    discard_fixed_overstep(&mut fixed_time);
+}
+
+/// Start the fixed timestep afresh, so that the movement steps at the same frames in a replay,
+/// however long the game took to get to gameplay.
+fn discard_fixed_overstep(fixed_time: &mut Time<Fixed>) {
+    let overstep = fixed_time.overstep();
+    fixed_time.discard_overstep(overstep);
+}
+
+fn finish_recording(mut recorder: ResMut<Recorder>) {
+    recorder.active = false;
+    let (Some(path), Some(recording)) = (&recorder.path, &recorder.recording) else {
+        return;
+    };
+    match recording.save(path) {
+        Ok(()) => info!("recorded game to {}", path.display()),
+        Err(err) => error!("could not write recording to {}: {err}", path.display()),
+    }
+}
+
+fn record_frame(time: Res<Time<Real>>, mut recorder: ResMut<Recorder>) {
+    if let Some(recording) = &mut recorder.recording {
+        recording.frames.push(time.delta().as_nanos() as u64);
+    }
+}
+
+fn record_keys(mut events: EventReader<KeyboardInput>, mut recorder: ResMut<Recorder>) {
+    for event in events.read() {
+        let text = match &event.logical_key {
+            Key::Character(text) => Some(text.to_string()),
+            _ => None,
+        };
+        recorder.push(ReplayInput::Key {
+            key: event.key_code,
+            text,
+            pressed: event.state.is_pressed(),
+        });
+    }
+}
+
+fn record_item(
+    trigger: Trigger<OnPress>,
+    items: Query<&Item, With<Button>>,
+    recorder: Option<ResMut<Recorder>>,
+) {
+    let (Ok(item), Some(mut recorder)) = (items.get(trigger.entity()), recorder) else {
+        return;
+    };
+    if recorder.active {
+        recorder.push(ReplayInput::UseItem(*item));
+    }
+}
+
+/// What the running dialogue waits for the player to do.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Waiting {
+    #[default]
+    Nothing,
+    Line,
+    Options,
+}
+
+impl Waiting {
+    /// Follow the dialogue, given which of its events were sent this frame.
+    fn update(&mut self, line: bool, options: bool, completed: bool) {
+        if line {
+            *self = Waiting::Line;
+        }
+        if options {
+            *self = Waiting::Options;
+        }
+        if completed {
+            *self = Waiting::Nothing;
+        }
+    }
+}
+
+/// Number keys pick options in the dialogue view.
+const OPTION_KEYS: [[KeyCode; 2]; 9] = [
+    [KeyCode::Digit1, KeyCode::Numpad1],
+    [KeyCode::Digit2, KeyCode::Numpad2],
+    [KeyCode::Digit3, KeyCode::Numpad3],
+    [KeyCode::Digit4, KeyCode::Numpad4],
+    [KeyCode::Digit5, KeyCode::Numpad5],
+    [KeyCode::Digit6, KeyCode::Numpad6],
+    [KeyCode::Digit7, KeyCode::Numpad7],
+    [KeyCode::Digit8, KeyCode::Numpad8],
+    [KeyCode::Digit9, KeyCode::Numpad9],
+];
+
+/// Whatever advanced the dialogue this frame, the dialogue view or anything else, is recorded by
+/// looking at the dialogue runner afterwards.
+fn record_dialogue(
+    mut lines: EventReader<PresentLineEvent>,
+    mut options: EventReader<PresentOptionsEvent>,
+    mut completed: EventReader<DialogueCompleteEvent>,
+    mut waiting: Local<Waiting>,
+    dialogue_runner: Query<&DialogueRunner>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    pressed_buttons: Query<(Entity, &Interaction, &Parent), (With<Button>, Changed<Interaction>)>,
+    children: Query<&Children>,
+    buttons: Query<(), With<Button>>,
+    mut recorder: ResMut<Recorder>,
+) {
+    waiting.update(
+        lines.read().count() > 0,
+        options.read().count() > 0,
+        completed.read().count() > 0,
+    );
+    let Ok(dialogue_runner) = dialogue_runner.get_single() else {
+        *waiting = Waiting::Nothing;
+        return;
+    };
+
+    match *waiting {
+        Waiting::Line if dialogue_runner.will_continue_in_next_update() => {
+            recorder.push(ReplayInput::Continue);
+        }
+        Waiting::Options if !dialogue_runner.is_waiting_for_option_selection() => {
+            let key_index = OPTION_KEYS
+                .iter()
+                .position(|keys_of_option| keys.any_just_pressed(keys_of_option.iter().copied()));
+            let gamepad_index = || {
+                OPTION_BUTTONS.iter().position(|button_type| {
+                    gamepads.iter().any(|gamepad| {
+                        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, *button_type))
+                    })
+                })
+            };
+            // Otherwise an option was clicked, and its button is among the buttons of all options.
+            let button_index = || {
+                let (entity, _, parent) = pressed_buttons
+                    .iter()
+                    .find(|(_, interaction, _)| **interaction == Interaction::Pressed)?;
+                children
+                    .get(parent.get())
+                    .ok()?
+                    .iter()
+                    .filter(|child| buttons.contains(**child))
+                    .position(|child| *child == entity)
+            };
+            match key_index.or_else(gamepad_index).or_else(button_index) {
+                Some(index) => recorder.push(ReplayInput::Choose(index)),
+                None => warn!("could not tell which option was chosen, not recording it"),
+            }
+        }
+        _ => return,
+    }
+    *waiting = Waiting::Nothing;
+}
+
+/// Set up the game like it was when it was recorded. The recorded keymap is only used, not saved.
+fn start_replay(
+    mut commands: Commands,
+    mut replayer: ResMut<Replayer>,
+    mut keymap: ResMut<Keymap>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    commands.insert_resource(replayer.recording.difficulty);
+    // This runs until the transition lets the screen change, so only the first run finds the
+    // player's keymap.
+    if replayer.keymap.is_none() {
+        let recorded_keymap = replayer.recording.keymap.clone();
+        replayer.keymap = Some(std::mem::replace(&mut *keymap, recorded_keymap));
+    }
+    if let Some(save) = &replayer.recording.save {
+        commands.insert_resource(LoadedSave(save.clone()));
+    }
+    next_screen.set(Screen::Gameplay);
+    if let Some(nanos) = replayer.recording.frames.first() {
+        commands.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_nanos(
+            *nanos,
+        )));
+    }
+}
+
+fn start_replay_frames(mut replayer: ResMut<Replayer>, mut fixed_time: ResMut<Time<Fixed>>) {
+    replayer.frame = Some(0);
+    discard_fixed_overstep(&mut fixed_time);
+}
+
+fn next_replay_frame(mut commands: Commands, mut replayer: ResMut<Replayer>) {
+    let frame = replayer.frame.map_or(0, |frame| frame + 1);
+    match replayer.recording.frames.get(frame) {
+        Some(nanos) => {
+            commands.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_nanos(
+                *nanos,
+            )));
+            replayer.frame = Some(frame);
+        }
+        None => {
+            info!("replay finished");
+            if let Some(keymap) = replayer.keymap.take() {
+                commands.insert_resource(keymap);
+            }
+            commands.insert_resource(TimeUpdateStrategy::Automatic);
+            commands.remove_resource::<Replayer>();
+        }
+    }
+}
+
+/// Replace whatever the keyboard and mouse did with the recorded keys.
+fn replay_keys(
+    replayer: Res<Replayer>,
+    mut keyboard: ResMut<Events<KeyboardInput>>,
+    mut mouse: ResMut<Events<MouseButtonInput>>,
+) {
+    keyboard.clear();
+    mouse.clear();
+    for input in replayer.inputs() {
+        let ReplayInput::Key { key, text, pressed } = input else {
+            continue;
+        };
+        keyboard.send(KeyboardInput {
+            key_code: *key,
+            logical_key: match text {
+                Some(text) => Key::Character(text.as_str().into()),
+                None => Key::Unidentified(NativeKey::Unidentified),
+            },
+            state: if *pressed {
+                ButtonState::Pressed
+            } else {
+                ButtonState::Released
+            },
+            window: Entity::PLACEHOLDER,
+        });
+    }
+}
+
+fn replay_items(
+    mut commands: Commands,
+    replayer: Res<Replayer>,
+    items: Query<(Entity, &Item), With<Button>>,
+) {
+    for input in replayer.inputs() {
+        let ReplayInput::UseItem(item) = input else {
+            continue;
+        };
+        match items.iter().find(|(_, i)| *i == item) {
+            Some((entity, _)) => commands.trigger_targets(OnPress, entity),
+            None => warn!("no {item} in the inventory to replay using it"),
+        }
+    }
+}
+
+/// Advance the dialogue like it was in the recording. An advance that comes before the dialogue is
+/// ready for it waits, and one that the dialogue view already did with the replayed keys is dropped.
+fn replay_dialogue(
+    mut lines: EventReader<PresentLineEvent>,
+    mut options: EventReader<PresentOptionsEvent>,
+    mut completed: EventReader<DialogueCompleteEvent>,
+    mut waiting: Local<Waiting>,
+    mut shown_options: Local<Vec<OptionId>>,
+    mut pending: Local<Vec<ReplayInput>>,
+    replayer: Res<Replayer>,
+    mut dialogue_runner: Query<&mut DialogueRunner>,
+) {
+    let last_options = options.read().last();
+    if let Some(event) = last_options {
+        *shown_options = event.options.iter().map(|option| option.id).collect();
+    }
+    waiting.update(
+        lines.read().count() > 0,
+        last_options.is_some(),
+        completed.read().count() > 0,
+    );
+    pending.extend(
+        replayer
+            .inputs()
+            .filter(|input| matches!(input, ReplayInput::Continue | ReplayInput::Choose(_)))
+            .cloned(),
+    );
+    let Ok(mut dialogue_runner) = dialogue_runner.get_single_mut() else {
+        return;
+    };
+
+    match (pending.first(), *waiting) {
+        (Some(ReplayInput::Continue), Waiting::Line) => {
+            if !dialogue_runner.will_continue_in_next_update() {
+                dialogue_runner.continue_in_next_update();
+            }
+        }
+        (Some(ReplayInput::Choose(index)), Waiting::Options) => {
+            if dialogue_runner.is_waiting_for_option_selection() {
+                match shown_options.get(*index) {
+                    Some(option) => {
+                        if let Err(err) = dialogue_runner.select_option(*option) {
+                            error!("could not replay choosing option {index}: {err}");
+                        }
+                    }
+                    None => error!("there is no option {index} to replay choosing it"),
+                }
+            }
+        }
+        _ => return,
+    }
+    pending.remove(0);
+    *waiting = Waiting::Nothing;
+}
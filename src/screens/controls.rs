@@ -0,0 +1,164 @@
+//! A screen to rebind the keys of every [`Action`], accessed from the title screen.
+
+use bevy::{input::keyboard::KeyboardInput, prelude::*, ui::Val::*};
+
+use crate::{
+    input::{key_name, Action, Keymap},
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Rebinding>();
+    app.add_systems(OnEnter(Screen::Controls), spawn_controls_screen);
+    app.add_systems(OnExit(Screen::Controls), |mut commands: Commands| {
+        commands.insert_resource(Rebinding::default())
+    });
+    app.add_systems(
+        Update,
+        (
+            capture_rebind,
+            update_binding_labels
+                .run_if(resource_changed::<Keymap>.or_else(resource_changed::<Rebinding>)),
+        )
+            .chain()
+            .run_if(in_state(Screen::Controls)),
+    );
+}
+
+/// The action and key slot that the next key press is bound to.
+#[derive(Resource, Debug, Default)]
+struct Rebinding(Option<RebindButton>);
+
+/// Rebinds one of the keys of the action, see [`Keymap::rebind`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct RebindButton {
+    action: Action,
+    slot: usize,
+}
+
+/// Explains why a key could not be bound.
+#[derive(Component, Debug)]
+struct ControlsMessage;
+
+fn spawn_controls_screen(mut commands: Commands, keymap: Res<Keymap>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Controls))
+        .with_children(|children| {
+            children.header("Controls");
+            for action in Action::ALL {
+                if action.is_dev() && !cfg!(feature = "dev") {
+                    continue;
+                }
+                children
+                    .spawn((
+                        Name::new("Binding Row"),
+                        NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                column_gap: Px(20.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ))
+                    .with_children(|row| {
+                        row.label(action.to_string());
+                        for slot in 0..keymap.slots(action) {
+                            let button = RebindButton { action, slot };
+                            row.button(button.describe(&keymap))
+                                .insert(button)
+                                .observe(start_rebinding);
+                        }
+                    });
+            }
+            children.label("").insert(ControlsMessage);
+
+            children.button("Reset").observe(reset_keymap);
+            children.button("Back").observe(enter_title_screen);
+        });
+}
+
+impl RebindButton {
+    /// The name of the key in the slot, or "-" if it is empty.
+    fn describe(self, keymap: &Keymap) -> String {
+        keymap
+            .keys(self.action)
+            .get(self.slot)
+            .map_or_else(|| "-".to_string(), |key| key_name(*key))
+    }
+}
+
+fn start_rebinding(
+    trigger: Trigger<OnPress>,
+    buttons: Query<&RebindButton>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    if let Ok(button) = buttons.get(trigger.entity()) {
+        rebinding.0 = Some(*button);
+    }
+}
+
+fn capture_rebind(
+    mut events: EventReader<KeyboardInput>,
+    mut rebinding: ResMut<Rebinding>,
+    mut keymap: ResMut<Keymap>,
+    mut message: Query<&mut Text, With<ControlsMessage>>,
+) {
+    let Some(button) = rebinding.0 else {
+        events.clear();
+        return;
+    };
+    let Some(key) = events
+        .read()
+        .filter(|event| event.state.is_pressed())
+        .map(|event| event.key_code)
+        .last()
+    else {
+        return;
+    };
+
+    let text = if key == KeyCode::Escape {
+        rebinding.0 = None;
+        String::new()
+    } else if let Some(other) = keymap.conflict(button.action, key) {
+        format!("{} is already used for {other}", key_name(key))
+    } else {
+        keymap.rebind(button.action, button.slot, key);
+        keymap.save();
+        rebinding.0 = None;
+        String::new()
+    };
+    for mut message in &mut message {
+        message.sections[0].value.clone_from(&text);
+    }
+}
+
+fn update_binding_labels(
+    keymap: Res<Keymap>,
+    rebinding: Res<Rebinding>,
+    buttons: Query<(&RebindButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (button, children) in &buttons {
+        let label = if rebinding.0 == Some(*button) {
+            "...".to_string()
+        } else {
+            button.describe(&keymap)
+        };
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value.clone_from(&label);
+        }
+    }
+}
+
+fn reset_keymap(_trigger: Trigger<OnPress>, mut keymap: ResMut<Keymap>) {
+    *keymap = Keymap::default();
+    keymap.save();
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
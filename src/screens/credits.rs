@@ -2,14 +2,24 @@
 
 use bevy::prelude::*;
 
-use crate::{asset_tracking::LoadResource, audio::Music, screens::Screen, theme::prelude::*};
+use crate::{
+    asset_tracking::LoadResource,
+    audio::{stop_music, MusicChannel, MusicManager},
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Credits), spawn_credits_screen);
 
     app.load_resource::<CreditsMusic>();
-    app.add_systems(OnEnter(Screen::Credits), play_credits_music);
-    app.add_systems(OnExit(Screen::Credits), stop_music);
+    app.add_systems(
+        OnEnter(Screen::Credits),
+        |music: Res<CreditsMusic>, mut manager: ResMut<MusicManager>| {
+            manager.play(MusicChannel::Music, music.music.clone());
+        },
+    );
+    app.add_systems(OnExit(Screen::Credits), stop_music(MusicChannel::Music));
 }
 
 fn spawn_credits_screen(mut commands: Commands) {
@@ -33,7 +43,6 @@ fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextSt
 pub struct CreditsMusic {
     #[dependency]
     music: Handle<AudioSource>,
-    entity: Option<Entity>,
 }
 
 impl FromWorld for CreditsMusic {
@@ -41,28 +50,6 @@ impl FromWorld for CreditsMusic {
         let assets = world.resource::<AssetServer>();
         Self {
             music: assets.load("audio/music/credits.ogg"),
-            entity: None,
         }
     }
 }
-
-fn play_credits_music(mut commands: Commands, mut music: ResMut<CreditsMusic>) {
-    music.entity = Some(
-        commands
-            .spawn((
-                AudioBundle {
-                    source: music.music.clone(),
-                    settings: PlaybackSettings::LOOP,
-                },
-                Music,
-                Name::from("Credits Music"),
-            ))
-            .id(),
-    );
-}
-
-fn stop_music(mut commands: Commands, mut music: ResMut<CreditsMusic>) {
-    if let Some(entity) = music.entity.take() {
-        commands.entity(entity).despawn_recursive();
-    }
-}
@@ -1,31 +1,73 @@
 //! The difficulty screen that appears when clicking play.
 
 use bevy::prelude::*;
+use derive_more::derive::Display;
+use serde::{Deserialize, Serialize};
 
 use crate::{screens::Screen, theme::prelude::*};
 
 pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Difficulty>();
+    app.init_resource::<Difficulty>();
     app.add_systems(OnEnter(Screen::Difficulty), spawn_difficulty_screen);
 }
 
+/// How hard the game is, chosen before starting it.
+/// Exposed to yarn as the `$difficulty` string variable.
+#[derive(
+    Resource, Reflect, Debug, Display, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+#[reflect(Resource)]
+pub enum Difficulty {
+    /// Gives hints, slows down the dino and nothing hurts.
+    Story,
+    #[default]
+    Medium,
+    /// The dino is quick to stomp and everything hurts twice as much.
+    Brutal,
+}
+
+impl Difficulty {
+    /// How fast the dino moves compared to [`Difficulty::Medium`].
+    pub fn dino_speed(self) -> f32 {
+        match self {
+            Difficulty::Story => 0.6,
+            Difficulty::Medium => 1.0,
+            Difficulty::Brutal => 1.5,
+        }
+    }
+
+    /// Factor applied to all damage the player takes.
+    pub fn damage_multiplier(self) -> f32 {
+        match self {
+            Difficulty::Story => 0.0,
+            Difficulty::Medium => 1.0,
+            Difficulty::Brutal => 2.0,
+        }
+    }
+}
+
 fn spawn_difficulty_screen(mut commands: Commands) {
     commands
         .ui_root()
         .insert(StateScoped(Screen::Difficulty))
         .with_children(|children| {
-            children.button("Story").observe(enter_gameplay_screen);
-            children.button("Medium").observe(enter_gameplay_screen);
-            children.button("Brutal").observe(enter_gameplay_screen);
+            for difficulty in [Difficulty::Story, Difficulty::Medium, Difficulty::Brutal] {
+                children.button(difficulty.to_string()).observe(
+                    move |_trigger: Trigger<OnPress>,
+                          mut commands: Commands,
+                          mut next_screen: ResMut<NextState<Screen>>| {
+                        commands.insert_resource(difficulty);
+                        next_screen.set(Screen::Gameplay);
+                    },
+                );
+            }
 
             children.label("");
             children.button("Back").observe(enter_title_screen);
         });
 }
 
-fn enter_gameplay_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
-    next_screen.set(Screen::Gameplay);
-}
-
 fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Title);
 }
@@ -2,14 +2,24 @@
 
 use bevy::prelude::*;
 
-use crate::{asset_tracking::LoadResource, audio::Music, screens::Screen, theme::prelude::*};
+use crate::{
+    asset_tracking::LoadResource,
+    audio::{stop_music, MusicChannel, MusicManager},
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::End), spawn_end_screen);
 
     app.load_resource::<EndMusic>();
-    app.add_systems(OnEnter(Screen::End), play_end_music);
-    app.add_systems(OnExit(Screen::End), stop_music);
+    app.add_systems(
+        OnEnter(Screen::End),
+        |music: Res<EndMusic>, mut manager: ResMut<MusicManager>| {
+            manager.play_once(MusicChannel::Music, music.music.clone());
+        },
+    );
+    app.add_systems(OnExit(Screen::End), stop_music(MusicChannel::Music));
 }
 
 fn spawn_end_screen(mut commands: Commands) {
@@ -33,7 +43,6 @@ fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextSt
 pub struct EndMusic {
     #[dependency]
     music: Handle<AudioSource>,
-    entity: Option<Entity>,
 }
 
 impl FromWorld for EndMusic {
@@ -41,28 +50,6 @@ impl FromWorld for EndMusic {
         let assets = world.resource::<AssetServer>();
         Self {
             music: assets.load("audio/sound_effects/end.ogg"),
-            entity: None,
         }
     }
 }
-
-fn play_end_music(mut commands: Commands, mut music: ResMut<EndMusic>) {
-    music.entity = Some(
-        commands
-            .spawn((
-                AudioBundle {
-                    source: music.music.clone(),
-                    settings: PlaybackSettings::ONCE,
-                },
-                Music,
-                Name::from("End Music"),
-            ))
-            .id(),
-    );
-}
-
-fn stop_music(mut commands: Commands, mut music: ResMut<EndMusic>) {
-    if let Some(entity) = music.entity.take() {
-        commands.entity(entity).despawn_recursive();
-    }
-}
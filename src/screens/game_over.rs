@@ -0,0 +1,43 @@
+//! A game over screen that is shown when the player runs out of health.
+
+use bevy::prelude::*;
+
+use crate::{
+    game::save::{LoadedSave, SaveData},
+    screens::Screen,
+    theme::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::GameOver), spawn_game_over_screen);
+}
+
+fn spawn_game_over_screen(mut commands: Commands) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::GameOver))
+        .with_children(|children| {
+            children.header("Wasted");
+            children.big_label("Wife sad.");
+            children.label("");
+
+            children.button("Try again").observe(retry);
+            children.button("Back").observe(enter_title_screen);
+        });
+}
+
+/// Continue from the last save, or start over if there is none.
+fn retry(
+    _trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    if let Some(save) = SaveData::load() {
+        commands.insert_resource(LoadedSave(save));
+    }
+    next_screen.set(Screen::Gameplay);
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
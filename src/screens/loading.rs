@@ -4,8 +4,12 @@
 use bevy::prelude::*;
 
 use crate::{
-    game::{fire::FireAssets, level::LevelAssets, player::PlayerAssets},
-    screens::{credits::CreditsMusic, gameplay::GameplayMusic, Screen},
+    audio::sound_bank::SoundBankAssets,
+    game::{
+        area::AreaAssets, fire::FireAssets, level::LevelAssets, player::PlayerAssets,
+        recipe::RecipeAssets, wife::WifeAssets,
+    },
+    screens::{credits::CreditsMusic, Screen},
     theme::{interaction::InteractionAssets, prelude::*},
 };
 
@@ -37,15 +41,21 @@ fn continue_to_title_screen(mut next_screen: ResMut<NextState<Screen>>) {
 fn all_assets_loaded(
     player_assets: Option<Res<PlayerAssets>>,
     level_assets: Option<Res<LevelAssets>>,
+    area_assets: Option<Res<AreaAssets>>,
     fire_assets: Option<Res<FireAssets>>,
+    wife_assets: Option<Res<WifeAssets>>,
+    recipe_assets: Option<Res<RecipeAssets>>,
+    sound_bank_assets: Option<Res<SoundBankAssets>>,
     interaction_assets: Option<Res<InteractionAssets>>,
     credits_music: Option<Res<CreditsMusic>>,
-    gameplay_music: Option<Res<GameplayMusic>>,
 ) -> bool {
     player_assets.is_some()
         && level_assets.is_some()
+        && area_assets.is_some()
         && fire_assets.is_some()
+        && wife_assets.is_some()
+        && recipe_assets.is_some()
+        && sound_bank_assets.is_some()
         && interaction_assets.is_some()
         && credits_music.is_some()
-        && gameplay_music.is_some()
 }
@@ -1,30 +1,44 @@
 //! The game's main screen states and transitions between them.
 
+mod controls;
 mod credits;
 mod difficulty;
 mod end;
+mod game_over;
 mod gameplay;
 mod loading;
+mod pause;
+mod settings;
 mod splash;
 mod title;
 
 use bevy::prelude::*;
 use derive_more::derive::Display;
+use serde::{Deserialize, Serialize};
+
+pub use difficulty::Difficulty;
+pub use pause::is_paused;
 
 pub(super) fn plugin(app: &mut App) {
     app.init_state::<Screen>();
     app.add_sub_state::<Area>();
+    app.add_sub_state::<Pause>();
     app.enable_state_scoped_entities::<Screen>();
     app.enable_state_scoped_entities::<Area>();
+    app.enable_state_scoped_entities::<Pause>();
 
     app.add_plugins((
+        controls::plugin,
         credits::plugin,
         gameplay::plugin,
         loading::plugin,
+        settings::plugin,
         splash::plugin,
         title::plugin,
         difficulty::plugin,
         end::plugin,
+        game_over::plugin,
+        pause::plugin,
     ));
 }
 
@@ -37,15 +51,34 @@ pub enum Screen {
     Loading,
     Title,
     Difficulty,
+    Controls,
+    Settings,
     Credits,
     Gameplay,
     End,
+    GameOver,
 }
 
-#[derive(SubStates, Debug, Hash, PartialEq, Eq, Clone, Copy, Default, Display)]
+#[derive(
+    SubStates, Debug, Hash, PartialEq, Eq, Clone, Copy, Default, Display, Serialize, Deserialize,
+)]
 #[source(Screen = Screen::Gameplay)]
 pub enum Area {
     Cave,
     #[default]
     Outside,
 }
+
+impl Area {
+    pub const ALL: [Area; 2] = [Area::Cave, Area::Outside];
+}
+
+/// Whether gameplay is running or which pause menu is open.
+#[derive(SubStates, Debug, Hash, PartialEq, Eq, Clone, Copy, Default)]
+#[source(Screen = Screen::Gameplay)]
+pub enum Pause {
+    #[default]
+    Running,
+    Menu,
+    Settings,
+}
@@ -0,0 +1,160 @@
+//! The pause menu during gameplay. Pausing stops virtual time, looping sounds and everything in
+//! [`AppSet::RecordInput`] and [`AppSet::Update`], but leaves [`ActionsFrozen`] alone so that a
+//! paused dialogue or cutscene picks up where it left off.
+//!
+//! [`ActionsFrozen`]: crate::game::movement::ActionsFrozen
+
+use bevy::{audio::PlaybackMode, prelude::*, ui::FocusPolicy};
+use bevy_yarnspinner_example_dialogue_view::ExampleYarnSpinnerDialogueViewSystemSet;
+
+use crate::{
+    audio::AudioSettings,
+    game::inventory::Paper,
+    input::{action_just_pressed, gamepad_just_pressed, Action},
+    screens::{Pause, Screen},
+    theme::prelude::*,
+    AppSet,
+};
+
+use super::settings::spawn_volume_sliders;
+
+pub(super) fn plugin(app: &mut App) {
+    app.configure_sets(
+        Update,
+        (
+            AppSet::RecordInput,
+            AppSet::Update,
+            ExampleYarnSpinnerDialogueViewSystemSet,
+        )
+            .run_if(not(is_paused)),
+    );
+
+    app.add_systems(
+        Update,
+        toggle_pause.run_if(
+            in_state(Screen::Gameplay).and_then(
+                action_just_pressed(Action::Cancel)
+                    .or_else(gamepad_just_pressed(GamepadButtonType::Start)),
+            ),
+        ),
+    );
+    // Leaving gameplay also exits `Pause::Running`, which isn't a pause.
+    app.add_systems(
+        OnExit(Pause::Running),
+        pause_game.run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(OnEnter(Pause::Running), resume_game);
+    app.add_systems(
+        OnExit(Screen::Gameplay),
+        |mut time: ResMut<Time<Virtual>>| time.unpause(),
+    );
+    app.add_systems(OnEnter(Pause::Menu), spawn_pause_menu);
+    app.add_systems(OnEnter(Pause::Settings), spawn_pause_settings);
+}
+
+/// A run condition that is true while a pause menu is open.
+pub fn is_paused(pause: Option<Res<State<Pause>>>) -> bool {
+    pause.is_some_and(|pause| *pause.get() != Pause::Running)
+}
+
+fn toggle_pause(
+    pause: Res<State<Pause>>,
+    mut next_pause: ResMut<NextState<Pause>>,
+    paper: Query<(), With<Paper>>,
+) {
+    next_pause.set(match pause.get() {
+        // Cancel closes the paper first.
+        Pause::Running if !paper.is_empty() => return,
+        Pause::Running => Pause::Menu,
+        Pause::Menu => Pause::Running,
+        Pause::Settings => Pause::Menu,
+    });
+}
+
+/// Marks a looping sound that was playing when the game was paused.
+#[derive(Component, Debug)]
+struct PausedSound;
+
+fn pause_game(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    sinks: Query<(Entity, &AudioSink, &PlaybackSettings)>,
+) {
+    time.pause();
+    for (entity, sink, playback) in &sinks {
+        if matches!(playback.mode, PlaybackMode::Loop) && !sink.is_paused() {
+            sink.pause();
+            commands.entity(entity).insert(PausedSound);
+        }
+    }
+}
+
+fn resume_game(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    sinks: Query<(Entity, &AudioSink), With<PausedSound>>,
+) {
+    time.unpause();
+    for (entity, sink) in &sinks {
+        sink.play();
+        commands.entity(entity).remove::<PausedSound>();
+    }
+}
+
+const PAUSE_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+fn spawn_pause_menu(mut commands: Commands) {
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(Pause::Menu),
+            BackgroundColor(PAUSE_BACKGROUND),
+            // Keep the inventory underneath from being clicked.
+            FocusPolicy::Block,
+            ZIndex::Global(1),
+        ))
+        .with_children(|children| {
+            children.header("Paused");
+            children.button("Resume").observe(resume);
+            children.button("Settings").observe(open_settings);
+            children.button("Restart").observe(restart);
+            children.button("Main Menu").observe(enter_title_screen);
+        });
+}
+
+fn spawn_pause_settings(mut commands: Commands, settings: Res<AudioSettings>) {
+    commands
+        .ui_root()
+        .insert((
+            StateScoped(Pause::Settings),
+            BackgroundColor(PAUSE_BACKGROUND),
+            FocusPolicy::Block,
+            ZIndex::Global(1),
+        ))
+        .with_children(|children| {
+            children.header("Settings");
+            spawn_volume_sliders(children, &settings);
+            children.button("Back").observe(close_settings);
+        });
+}
+
+fn resume(_trigger: Trigger<OnPress>, mut next_pause: ResMut<NextState<Pause>>) {
+    next_pause.set(Pause::Running);
+}
+
+fn open_settings(_trigger: Trigger<OnPress>, mut next_pause: ResMut<NextState<Pause>>) {
+    next_pause.set(Pause::Settings);
+}
+
+fn close_settings(_trigger: Trigger<OnPress>, mut next_pause: ResMut<NextState<Pause>>) {
+    next_pause.set(Pause::Menu);
+}
+
+/// Start a new game, beginning with the choice of difficulty.
+fn restart(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Difficulty);
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
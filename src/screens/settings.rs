@@ -0,0 +1,109 @@
+//! A settings screen with volume sliders, accessed from the title screen.
+//! The sliders can also be spawned into other menus with [`spawn_volume_sliders`].
+
+use bevy::{prelude::*, ui::Val::*};
+
+use crate::{audio::AudioSettings, screens::Screen, theme::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Settings), spawn_settings_screen);
+    app.add_systems(Update, (apply_volume_sliders, save_volume_settings).chain());
+}
+
+fn spawn_settings_screen(mut commands: Commands, settings: Res<AudioSettings>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Settings))
+        .with_children(|children| {
+            children.header("Settings");
+            spawn_volume_sliders(children, &settings);
+            children.button("Back").observe(enter_title_screen);
+        });
+}
+
+/// The [`AudioSettings`] volume that a [`Slider`] controls.
+#[derive(Component, Debug, Clone, Copy)]
+enum VolumeSlider {
+    Master,
+    Music,
+    SoundEffects,
+}
+
+impl VolumeSlider {
+    fn volume(self, settings: &mut AudioSettings) -> &mut f32 {
+        match self {
+            VolumeSlider::Master => &mut settings.master,
+            VolumeSlider::Music => &mut settings.music,
+            VolumeSlider::SoundEffects => &mut settings.sound_effects,
+        }
+    }
+}
+
+/// Spawn a labeled slider for each volume in the [`AudioSettings`].
+pub fn spawn_volume_sliders(children: &mut ChildBuilder, settings: &AudioSettings) {
+    for (name, slider, value) in [
+        ("Master", VolumeSlider::Master, settings.master),
+        ("Music", VolumeSlider::Music, settings.music),
+        (
+            "Sound effects",
+            VolumeSlider::SoundEffects,
+            settings.sound_effects,
+        ),
+    ] {
+        children
+            .spawn((
+                Name::new("Volume Row"),
+                NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        column_gap: Px(20.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|row| {
+                row.label(name).insert(Style {
+                    width: Px(200.0),
+                    ..default()
+                });
+                row.slider(value).insert(slider);
+            });
+    }
+}
+
+fn apply_volume_sliders(
+    sliders: Query<(&VolumeSlider, &Slider), Changed<Slider>>,
+    mut settings: ResMut<AudioSettings>,
+) {
+    for (volume_slider, slider) in &sliders {
+        let volume = volume_slider.volume(settings.bypass_change_detection());
+        if *volume != slider.value {
+            *volume = slider.value;
+            settings.set_changed();
+        }
+    }
+}
+
+/// Write the settings once the player lets go of the slider, not on every step of a drag.
+/// Gamepads change the volume in steps, so each of them is saved.
+fn save_volume_settings(
+    mut unsaved: Local<bool>,
+    settings: Res<AudioSettings>,
+    sliders: Query<&Interaction, With<VolumeSlider>>,
+) {
+    if settings.is_changed() && !settings.is_added() {
+        *unsaved = true;
+    }
+    let dragging = sliders
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    if *unsaved && !dragging {
+        settings.save();
+        *unsaved = false;
+    }
+}
+
+fn enter_title_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
@@ -1,12 +1,17 @@
 //! A splash screen that plays briefly at startup.
 
 use bevy::{
-    input::common_conditions::input_just_pressed,
     prelude::*,
     render::texture::{ImageLoaderSettings, ImageSampler},
 };
 
-use crate::{screens::Screen, theme::prelude::*, AppSet};
+use crate::{
+    input::{action_just_pressed, Action},
+    screens::Screen,
+    theme::prelude::*,
+    transition::fade_in_out,
+    AppSet,
+};
 
 pub(super) fn plugin(app: &mut App) {
     // Spawn splash screen.
@@ -36,11 +41,11 @@ pub(super) fn plugin(app: &mut App) {
             .run_if(in_state(Screen::Splash)),
     );
 
-    // Exit the splash screen early if the player hits escape.
+    // Exit the splash screen early if the player hits cancel.
     app.add_systems(
         Update,
         continue_to_loading_screen
-            .run_if(input_just_pressed(KeyCode::Escape).and_then(in_state(Screen::Splash))),
+            .run_if(action_just_pressed(Action::Cancel).and_then(in_state(Screen::Splash))),
     );
 }
 
@@ -99,12 +104,7 @@ struct UiImageFadeInOut {
 
 impl UiImageFadeInOut {
     fn alpha(&self) -> f32 {
-        // Normalize by duration.
-        let t = (self.t / self.total_duration).clamp(0.0, 1.0);
-        let fade = self.fade_duration / self.total_duration;
-
-        // Regular trapezoid-shaped graph, flat at the top with alpha = 1.0.
-        ((1.0 - (2.0 * t - 1.0).abs()) / fade).min(1.0)
+        fade_in_out(self.t, self.total_duration, self.fade_duration)
     }
 }
 
@@ -2,7 +2,11 @@
 
 use bevy::prelude::*;
 
-use crate::{screens::Screen, theme::prelude::*};
+use crate::{
+    game::save::{LoadedSave, SaveData},
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), spawn_title_screen);
@@ -13,7 +17,12 @@ fn spawn_title_screen(mut commands: Commands) {
         .ui_root()
         .insert(StateScoped(Screen::Title))
         .with_children(|children| {
+            if SaveData::load().is_some() {
+                children.button("Continue").observe(continue_game);
+            }
             children.button("Play").observe(enter_difficulty_screen);
+            children.button("Settings").observe(enter_settings_screen);
+            children.button("Controls").observe(enter_controls_screen);
             children.button("Credits").observe(enter_credits_screen);
 
             #[cfg(not(target_family = "wasm"))]
@@ -25,6 +34,26 @@ fn enter_difficulty_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<N
     next_screen.set(Screen::Difficulty);
 }
 
+fn continue_game(
+    _trigger: Trigger<OnPress>,
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let Some(save) = SaveData::load() else {
+        return;
+    };
+    commands.insert_resource(LoadedSave(save));
+    next_screen.set(Screen::Gameplay);
+}
+
+fn enter_settings_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Settings);
+}
+
+fn enter_controls_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Controls);
+}
+
 fn enter_credits_screen(_trigger: Trigger<OnPress>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Credits);
 }
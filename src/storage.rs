@@ -0,0 +1,111 @@
+//! Persist small text blobs like save games and settings between sessions.
+//! Native builds write files to the platform's data directory, web builds use `localStorage`.
+//! Tests keep everything in memory, so they neither see nor overwrite the player's data.
+
+use bevy::prelude::*;
+
+/// Read the value stored under `key`, if there is one.
+pub fn read(key: &str) -> Option<String> {
+    platform::read(key)
+}
+
+/// Store `value` under `key`, replacing any previous value.
+pub fn write(key: &str, value: &str) {
+    if let Err(err) = platform::write(key, value) {
+        warn!("could not write {key}: {err}");
+    }
+}
+
+/// Remove the value stored under `key`.
+pub fn remove(key: &str) {
+    if let Err(err) = platform::remove(key) {
+        warn!("could not remove {key}: {err}");
+    }
+}
+
+#[cfg(all(not(target_family = "wasm"), not(test)))]
+mod platform {
+    use std::{fs, io, path::PathBuf};
+
+    use directories::ProjectDirs;
+
+    fn path(key: &str) -> io::Result<PathBuf> {
+        let dirs = ProjectDirs::from("xyz", "Jokler", "Thanks Wife").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no home directory to store data in",
+            )
+        })?;
+        Ok(dirs.data_dir().join(format!("{key}.ron")))
+    }
+
+    pub fn read(key: &str) -> Option<String> {
+        fs::read_to_string(path(key).ok()?).ok()
+    }
+
+    pub fn write(key: &str, value: &str) -> io::Result<()> {
+        let path = path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, value)
+    }
+
+    pub fn remove(key: &str) -> io::Result<()> {
+        match fs::remove_file(path(key)?) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(target_family = "wasm", not(test)))]
+mod platform {
+    use web_sys::Storage;
+
+    fn local_storage() -> Result<Storage, String> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| "localStorage is not available".to_string())
+    }
+
+    pub fn read(key: &str) -> Option<String> {
+        local_storage().ok()?.get_item(key).ok().flatten()
+    }
+
+    pub fn write(key: &str, value: &str) -> Result<(), String> {
+        local_storage()?
+            .set_item(key, value)
+            .map_err(|err| format!("{err:?}"))
+    }
+
+    pub fn remove(key: &str) -> Result<(), String> {
+        local_storage()?
+            .remove_item(key)
+            .map_err(|err| format!("{err:?}"))
+    }
+}
+
+#[cfg(test)]
+mod platform {
+    use std::{collections::BTreeMap, convert::Infallible, sync::Mutex};
+
+    static VALUES: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+    pub fn read(key: &str) -> Option<String> {
+        VALUES.lock().unwrap().get(key).cloned()
+    }
+
+    pub fn write(key: &str, value: &str) -> Result<(), Infallible> {
+        VALUES
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    pub fn remove(key: &str) -> Result<(), Infallible> {
+        VALUES.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+//! Setup shared by the tests of several modules.
+
+use bevy::{prelude::*, state::app::StatesPlugin};
+use bevy_yarnspinner::prelude::{YarnFile, YarnProject, YarnSpinnerPlugin};
+
+/// Give up waiting for the yarn project after a minute of updates.
+const MAX_UPDATES: u32 = 60 * 60;
+
+/// An app without a window or renderer that can load assets and change states.
+pub(crate) fn minimal_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, AssetPlugin::default()));
+    app
+}
+
+/// Update `app` until its yarn files have compiled.
+pub(crate) fn wait_for_yarn_project(app: &mut App) {
+    for _ in 0..MAX_UPDATES {
+        if app.world().contains_resource::<YarnProject>() {
+            return;
+        }
+        app.update();
+    }
+    panic!("the yarn project did not compile");
+}
+
+/// A [`minimal_app`] that has compiled `source` as `test.yarn`.
+pub(crate) fn yarn_app(source: &str) -> App {
+    let mut app = minimal_app();
+    app.add_plugins(YarnSpinnerPlugin::with_yarn_source(YarnFile::new(
+        "test.yarn",
+        source,
+    )));
+    wait_for_yarn_project(&mut app);
+    app
+}
@@ -0,0 +1,407 @@
+//! Headless playthroughs of the whole game. The app runs without a window, renderer or dialogue
+//! view: keys are pressed by sending [`KeyboardInput`] events, and dialogue continues on its own
+//! and always picks the first option, unless a recorded game is replayed.
+
+use std::time::Duration;
+
+use bevy::{
+    audio::AudioPlugin,
+    input::{
+        keyboard::{Key, KeyboardInput, NativeKey},
+        ButtonState, InputPlugin,
+    },
+    prelude::*,
+    render::{mesh::Mesh, render_resource::Shader, texture::ImagePlugin},
+    sprite::SpritePlugin,
+    text::TextPlugin,
+    time::TimeUpdateStrategy,
+    ui::UiPlugin,
+};
+use bevy_yarnspinner::{
+    events::{PresentLineEvent, PresentOptionsEvent},
+    prelude::{DialogueRunner, YarnSpinnerSystemSet, YarnValue},
+};
+
+use crate::{
+    game::{
+        inventory::{Inventory, Item},
+        level::Level,
+        movement::{ActionsFrozen, MovementController},
+        player::Player,
+    },
+    input::{Action, Keymap},
+    replay::{Recorder, Recording, Replayer},
+    screens::{Area, Difficulty, Screen},
+    test_support::{minimal_app, wait_for_yarn_project},
+    theme::prelude::*,
+    GamePlugin,
+};
+
+/// How much time passes per update, no matter how fast the test runs.
+const FRAME: Duration = Duration::from_micros(16_667);
+/// Give up waiting for something after a minute of game time.
+const MAX_FRAMES: u32 = 60 * 60;
+
+struct Playthrough {
+    app: App,
+}
+
+impl Playthrough {
+    /// Load the game, which then waits on the title screen.
+    fn new() -> Self {
+        let mut app = headless_app();
+        app.add_systems(Update, answer_dialogue.after(YarnSpinnerSystemSet));
+        Self::load(app)
+    }
+
+    /// Load the game with the player's `keymap` and let it replay the recording.
+    fn replay(recording: Recording, keymap: Keymap) -> Self {
+        let mut app = headless_app();
+        app.insert_resource(keymap);
+        app.insert_resource(Replayer::new(recording));
+        Self::load(app)
+    }
+
+    fn load(mut app: App) -> Self {
+        app.finish();
+        app.cleanup();
+        wait_for_yarn_project(&mut app);
+        Self { app }
+    }
+
+    /// Start a new game from the title screen and sit through the intro.
+    fn start(&mut self, difficulty: Difficulty) {
+        self.run_until("the title screen", |world| {
+            *world.resource::<State<Screen>>() == Screen::Title
+        });
+        let world = self.app.world_mut();
+        world.insert_resource(difficulty);
+        world
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Gameplay);
+        self.run_until("gameplay", |world| {
+            *world.resource::<State<Screen>>() == Screen::Gameplay
+        });
+        self.settle();
+    }
+
+    fn run_until(&mut self, what: &str, mut done: impl FnMut(&mut World) -> bool) {
+        for _ in 0..MAX_FRAMES {
+            if done(self.app.world_mut()) {
+                return;
+            }
+            self.app.update();
+        }
+        panic!("gave up waiting for {what}");
+    }
+
+    /// Wait until no dialogue is running and the player can move again.
+    fn settle(&mut self) {
+        self.run_until("the player to be able to move", |world| {
+            let talking = world
+                .query::<&DialogueRunner>()
+                .iter(world)
+                .any(DialogueRunner::is_running);
+            !talking && !world.resource::<ActionsFrozen>().is_frozen()
+        });
+    }
+
+    fn send_key(&mut self, key_code: KeyCode, logical_key: Key, state: ButtonState) {
+        self.app.world_mut().send_event(KeyboardInput {
+            key_code,
+            logical_key,
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+    }
+
+    fn hold(&mut self, key: KeyCode) {
+        let logical_key = Key::Unidentified(NativeKey::Unidentified);
+        self.send_key(key, logical_key, ButtonState::Pressed);
+    }
+
+    fn release(&mut self, key: KeyCode) {
+        let logical_key = Key::Unidentified(NativeKey::Unidentified);
+        self.send_key(key, logical_key, ButtonState::Released);
+    }
+
+    fn tap(&mut self, key: KeyCode) {
+        self.hold(key);
+        self.app.update();
+        self.release(key);
+        self.app.update();
+    }
+
+    /// Press a key that types `character`, e.g. on the paper.
+    fn type_key(&mut self, key: KeyCode, character: &str) {
+        self.send_key(key, Key::Character(character.into()), ButtonState::Pressed);
+        self.app.update();
+        self.send_key(key, Key::Character(character.into()), ButtonState::Released);
+        self.app.update();
+    }
+
+    /// Interact with whatever the player stands next to and sit through the dialogue it starts.
+    fn interact(&mut self) {
+        self.tap(KeyCode::KeyE);
+        self.settle();
+    }
+
+    /// Click the item in the inventory.
+    fn use_item(&mut self, item: Item) {
+        let world = self.app.world_mut();
+        let entity = world
+            .query_filtered::<(Entity, &Item), With<Button>>()
+            .iter(world)
+            .find(|(_, i)| **i == item)
+            .map(|(entity, _)| entity)
+            .unwrap_or_else(|| panic!("no {item} in the inventory"));
+        world.trigger_targets(OnPress, entity);
+        self.app.update();
+    }
+
+    fn player_x(&mut self) -> f32 {
+        player_x(self.app.world_mut())
+    }
+
+    /// Jump and wait until the player stands on something again.
+    fn jump(&mut self) {
+        self.tap(KeyCode::Space);
+        self.run_until("the player to jump", |world| !player_grounded(world));
+        self.run_until("the player to land", player_grounded);
+    }
+
+    /// Walk until the player reaches `x`.
+    fn walk_to(&mut self, x: f32) {
+        let starts_left = self.player_x() < x;
+        let key = if starts_left {
+            KeyCode::KeyD
+        } else {
+            KeyCode::KeyA
+        };
+        self.hold(key);
+        self.run_until(&format!("the player to walk to {x}"), |world| {
+            (player_x(world) < x) != starts_left
+        });
+        self.release(key);
+        self.app.update();
+    }
+
+    /// Walk through the edge of the current area into `area`. The cave is left of the outside.
+    fn walk_to_area(&mut self, area: Area) {
+        let key = match area {
+            Area::Cave => KeyCode::KeyA,
+            Area::Outside => KeyCode::KeyD,
+        };
+        self.hold(key);
+        self.run_until(&format!("the player to walk to the {area}"), |world| {
+            *world.resource::<State<Area>>() == area
+        });
+        self.release(key);
+        self.settle();
+    }
+
+    fn has(&self, item: Item) -> bool {
+        self.app
+            .world()
+            .resource::<Inventory>()
+            .items
+            .contains(&item)
+    }
+
+    fn lies_around(&self, item: Item) -> bool {
+        self.app.world().resource::<Level>().items.contains(&item)
+    }
+
+    fn variable(&mut self, name: &str) -> Option<YarnValue> {
+        let world = self.app.world_mut();
+        world
+            .query::<&DialogueRunner>()
+            .single(world)
+            .variable_storage()
+            .get(name)
+            .ok()
+    }
+
+    fn screen(&self) -> Screen {
+        *self.app.world().resource::<State<Screen>>().get()
+    }
+}
+
+/// The game in an app without a window, renderer or gamepads, where every update takes the same
+/// time. It has only the plugins for the input, assets and components that the game uses.
+fn headless_app() -> App {
+    let mut app = minimal_app();
+    app.add_plugins((
+        TransformPlugin,
+        HierarchyPlugin,
+        InputPlugin,
+        WindowPlugin::default(),
+        AudioPlugin::default(),
+        ImagePlugin::default(),
+    ));
+    // Sprites and UI load their shaders and meshes as assets, even without a renderer.
+    app.init_asset::<Shader>();
+    app.init_asset::<Mesh>();
+    app.add_plugins((SpritePlugin, TextPlugin, UiPlugin));
+    app.add_plugins(GamePlugin);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+    app
+}
+
+fn player_grounded(world: &mut World) -> bool {
+    world
+        .query_filtered::<&MovementController, With<Player>>()
+        .single(world)
+        .grounded
+}
+
+fn player_y(world: &mut World) -> f32 {
+    world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world)
+        .translation
+        .y
+}
+
+fn player_x(world: &mut World) -> f32 {
+    world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world)
+        .translation
+        .x
+}
+
+/// Stands in for the dialogue view: continue after every line and choose the first option.
+/// Like the view, it answers after the dialogue runner.
+fn answer_dialogue(
+    mut lines: EventReader<PresentLineEvent>,
+    mut options: EventReader<PresentOptionsEvent>,
+    mut dialogue_runner: Query<&mut DialogueRunner>,
+) {
+    let Ok(mut dialogue_runner) = dialogue_runner.get_single_mut() else {
+        return;
+    };
+    for _ in lines.read() {
+        dialogue_runner.continue_in_next_update();
+    }
+    for event in options.read() {
+        let option = event.options.first().expect("options to choose from");
+        dialogue_runner
+            .select_option(option.id)
+            .expect("the first option can be chosen");
+    }
+}
+
+/// Walk the solution path from the start of the game to the end screen.
+fn solve_the_puzzle(game: &mut Playthrough) {
+    // The knife lies in the cave, where the wife first reminds the player of the plant.
+    game.walk_to_area(Area::Cave);
+    game.walk_to(295.0);
+    game.interact();
+    assert!(game.has(Item::Knife));
+    game.walk_to(-400.0);
+    game.interact();
+
+    // Take the papyrus and the banana outside and cut the papyrus with the knife.
+    game.walk_to_area(Area::Outside);
+    game.walk_to(170.0);
+    game.interact();
+    assert!(game.has(Item::Papyrus));
+    // The banana hangs in the palm tree, out of reach from the ground.
+    game.walk_to(460.0);
+    game.jump();
+    assert!(player_y(game.app.world_mut()) > 0.0);
+    game.interact();
+    assert!(game.has(Item::Banana));
+    game.use_item(Item::Papyrus);
+    game.settle();
+    assert!(game.has(Item::PapyrusStrips));
+
+    // The wife weaves the strips.
+    game.walk_to_area(Area::Cave);
+    game.walk_to(-400.0);
+    game.interact();
+    assert!(game.has(Item::WovenPapyrus));
+
+    // Carrying the woven papyrus outside calls the dino, which stomps it into paper.
+    game.walk_to_area(Area::Outside);
+    game.hold(KeyCode::KeyD);
+    game.run_until("the dino to stomp the papyrus into paper", |world| {
+        world.resource::<Level>().items.contains(&Item::Paper)
+    });
+    game.release(KeyCode::KeyD);
+    game.settle();
+    assert!(!game.has(Item::WovenPapyrus));
+    game.walk_to(-300.0);
+    game.interact();
+    assert!(game.has(Item::Paper));
+
+    // Burn the banana in the fire and pick it up again.
+    game.walk_to_area(Area::Cave);
+    game.walk_to(-80.0);
+    game.interact();
+    assert!(!game.has(Item::Banana));
+    assert!(game.lies_around(Item::BurntBanana));
+    game.walk_to(0.0);
+    game.interact();
+    assert!(game.has(Item::BurntBanana));
+
+    // The wife explains that the burnt banana is a pen.
+    game.walk_to(-400.0);
+    game.interact();
+    assert_eq!(
+        game.variable("$learned_pen"),
+        Some(YarnValue::Boolean(true))
+    );
+
+    // Write on the paper.
+    game.use_item(Item::Paper);
+    game.type_key(KeyCode::KeyH, "h");
+    game.type_key(KeyCode::KeyI, "i");
+    game.tap(KeyCode::Escape);
+    assert!(game.has(Item::WrittenPaper));
+    assert!(!game.has(Item::Paper));
+
+    // Hand it to the wife.
+    game.tap(KeyCode::KeyE);
+    game.run_until("the end screen", |world| {
+        *world.resource::<State<Screen>>() == Screen::End
+    });
+    assert_eq!(game.screen(), Screen::End);
+}
+
+#[test]
+fn play_through_the_puzzle() {
+    let mut game = Playthrough::new();
+    game.start(Difficulty::Medium);
+    solve_the_puzzle(&mut game);
+}
+
+#[test]
+fn replay_a_recorded_playthrough() {
+    let mut game = Playthrough::new();
+    game.app.insert_resource(Recorder::default());
+    game.start(Difficulty::Medium);
+    solve_the_puzzle(&mut game);
+    let recording = game
+        .app
+        .world_mut()
+        .resource_mut::<Recorder>()
+        .recording
+        .take()
+        .expect("the game was recorded");
+
+    // Nothing answers the dialogue this time, only the recording does. The keys are bound
+    // differently here, but the replay plays with the recorded ones.
+    let mut rebound = Keymap::default();
+    rebound.rebind(Action::MoveRight, 0, KeyCode::KeyL);
+    let mut replay = Playthrough::replay(recording, rebound.clone());
+    replay.run_until("the replay to reach the end screen", |world| {
+        *world.resource::<State<Screen>>() == Screen::End
+    });
+    assert!(replay.has(Item::WrittenPaper));
+    replay.run_until("the replay to finish", |world| {
+        !world.contains_resource::<Replayer>()
+    });
+    assert_eq!(*replay.app.world().resource::<Keymap>(), rebound);
+}
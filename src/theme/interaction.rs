@@ -1,17 +1,30 @@
 use bevy::prelude::*;
 
-use crate::{asset_tracking::LoadResource, audio::SoundEffect};
+use crate::{
+    asset_tracking::LoadResource,
+    audio::SoundEffect,
+    input::{gamepad_just_pressed, InputDevice},
+    theme::slider::Slider,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<InteractionPalette>();
     app.load_resource::<InteractionAssets>();
+    app.init_resource::<FocusedButton>();
     app.add_systems(
         Update,
         (
+            (
+                clear_focus,
+                navigate_focus,
+                press_focused.run_if(gamepad_just_pressed(GamepadButtonType::South)),
+            )
+                .chain(),
             trigger_on_press,
             apply_interaction_palette,
             trigger_interaction_sound_effect,
         )
+            .chain()
             .run_if(resource_exists::<InteractionAssets>),
     );
 }
@@ -43,14 +56,122 @@ fn trigger_on_press(
     }
 }
 
+/// Buttons with this component can be focused and pressed with a gamepad.
+#[derive(Component, Debug, Default)]
+pub struct Focusable;
+
+/// The [`Focusable`] button that the gamepad is currently on. It looks hovered.
+#[derive(Resource, Debug, Default)]
+pub struct FocusedButton(pub Option<Entity>);
+
+/// Sticks have to be tilted this far to move the focus.
+const STICK_THRESHOLD: f32 = 0.5;
+
+fn clear_focus(
+    mut focused: ResMut<FocusedButton>,
+    device: Res<InputDevice>,
+    focusables: Query<(), With<Focusable>>,
+) {
+    let Some(entity) = focused.0 else {
+        return;
+    };
+    if *device == InputDevice::Keyboard || !focusables.contains(entity) {
+        focused.0 = None;
+    }
+}
+
+/// Move the focus through the buttons from top to bottom with the D-pad or left stick.
+/// Left and right adjust a focused [`Slider`] instead.
+fn navigate_focus(
+    mut commands: Commands,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut stick_held: Local<bool>,
+    mut focused: ResMut<FocusedButton>,
+    focusables: Query<(Entity, &GlobalTransform), With<Focusable>>,
+    sliders: Query<(), With<Slider>>,
+    interaction_assets: Res<InteractionAssets>,
+) {
+    let on_slider = focused.0.is_some_and(|entity| sliders.contains(entity));
+    let mut step = 0;
+    let mut stick_tilted = false;
+    for gamepad in gamepads.iter() {
+        let just_pressed =
+            |button_type| buttons.just_pressed(GamepadButton::new(gamepad, button_type));
+        if just_pressed(GamepadButtonType::DPadDown)
+            || (just_pressed(GamepadButtonType::DPadRight) && !on_slider)
+        {
+            step += 1;
+        }
+        if just_pressed(GamepadButtonType::DPadUp)
+            || (just_pressed(GamepadButtonType::DPadLeft) && !on_slider)
+        {
+            step -= 1;
+        }
+
+        let stick_y = axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+            .unwrap_or_default();
+        if stick_y.abs() > STICK_THRESHOLD {
+            stick_tilted = true;
+            if !*stick_held {
+                // Tilting the stick up moves the focus up, which is towards the first button.
+                step -= stick_y.signum() as i32;
+            }
+        }
+    }
+    *stick_held = stick_tilted;
+    if step == 0 {
+        return;
+    }
+
+    let mut ordered = focusables.iter().collect::<Vec<_>>();
+    if ordered.is_empty() {
+        return;
+    }
+    // UI coordinates grow downwards, so this is reading order.
+    ordered.sort_by(|(_, a), (_, b)| {
+        let (a, b) = (a.translation(), b.translation());
+        a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x))
+    });
+    let next = match focused
+        .0
+        .and_then(|focused| ordered.iter().position(|(entity, _)| *entity == focused))
+    {
+        Some(index) => (index as i32 + step).rem_euclid(ordered.len() as i32) as usize,
+        None => 0,
+    };
+    focused.0 = Some(ordered[next].0);
+    spawn_button_sound(&mut commands, interaction_assets.hover.clone());
+}
+
+fn press_focused(
+    mut commands: Commands,
+    focused: Res<FocusedButton>,
+    interaction_assets: Res<InteractionAssets>,
+) {
+    if let Some(entity) = focused.0 {
+        commands.trigger_targets(OnPress, entity);
+        spawn_button_sound(&mut commands, interaction_assets.press.clone());
+    }
+}
+
 fn apply_interaction_palette(
-    mut palette_query: Query<
-        (&Interaction, &InteractionPalette, &mut BackgroundColor),
-        Changed<Interaction>,
-    >,
+    focused: Res<FocusedButton>,
+    mut palette_query: Query<(
+        Entity,
+        Ref<Interaction>,
+        &InteractionPalette,
+        &mut BackgroundColor,
+    )>,
 ) {
-    for (interaction, palette, mut background) in &mut palette_query {
-        *background = match interaction {
+    for (entity, interaction, palette, mut background) in &mut palette_query {
+        if !interaction.is_changed() && !focused.is_changed() {
+            continue;
+        }
+        *background = match *interaction {
+            Interaction::None if focused.0 == Some(entity) => palette.hovered,
             Interaction::None => palette.none,
             Interaction::Hovered => palette.hovered,
             Interaction::Pressed => palette.pressed,
@@ -93,13 +214,17 @@ fn trigger_interaction_sound_effect(
             Interaction::Pressed => interaction_assets.press.clone(),
             _ => continue,
         };
-        commands.spawn((
-            AudioBundle {
-                source,
-                settings: PlaybackSettings::DESPAWN,
-            },
-            SoundEffect,
-            Name::from("Button Sound"),
-        ));
+        spawn_button_sound(&mut commands, source);
     }
 }
+
+fn spawn_button_sound(commands: &mut Commands, source: Handle<AudioSource>) {
+    commands.spawn((
+        AudioBundle {
+            source,
+            settings: PlaybackSettings::DESPAWN,
+        },
+        SoundEffect,
+        Name::from("Button Sound"),
+    ));
+}
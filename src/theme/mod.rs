@@ -5,13 +5,15 @@
 
 pub mod interaction;
 pub mod palette;
+pub mod slider;
 mod widgets;
 
 #[allow(unused_imports)]
 pub mod prelude {
     pub use super::{
-        interaction::{InteractionPalette, OnPress},
+        interaction::{Focusable, InteractionPalette, OnPress},
         palette as ui_palette,
+        slider::Slider,
         widgets::{Containers as _, Widgets as _},
     };
 }
@@ -19,5 +21,5 @@ pub mod prelude {
 use bevy::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(interaction::plugin);
+    app.add_plugins((interaction::plugin, slider::plugin));
 }
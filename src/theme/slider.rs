@@ -0,0 +1,100 @@
+use bevy::{prelude::*, ui::RelativeCursorPosition};
+
+use super::interaction::FocusedButton;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Slider>();
+    app.add_systems(
+        Update,
+        ((drag_slider, step_focused_slider), update_slider_fill).chain(),
+    );
+}
+
+/// How much a gamepad changes the value of a focused [`Slider`] per step.
+const GAMEPAD_STEP: f32 = 0.1;
+
+/// Sticks have to be tilted this far to step the slider.
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// A value between 0 and 1 that the player sets by clicking or dragging along the slider,
+/// or with left and right on a gamepad while it is focused.
+/// Spawn one with [`Widgets::slider`](super::widgets::Widgets::slider).
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Slider {
+    pub value: f32,
+}
+
+/// The part of the [`Slider`] that fills up with its value.
+#[derive(Component, Debug)]
+pub struct SliderFill;
+
+fn drag_slider(mut sliders: Query<(&Interaction, &RelativeCursorPosition, &mut Slider)>) {
+    for (interaction, cursor, mut slider) in &mut sliders {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+        let value = position.x.clamp(0.0, 1.0);
+        if slider.value != value {
+            slider.value = value;
+        }
+    }
+}
+
+fn step_focused_slider(
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut stick_held: Local<bool>,
+    focused: Res<FocusedButton>,
+    mut sliders: Query<&mut Slider>,
+) {
+    let mut step = 0.0;
+    let mut stick_tilted = false;
+    for gamepad in gamepads.iter() {
+        let just_pressed =
+            |button_type| buttons.just_pressed(GamepadButton::new(gamepad, button_type));
+        if just_pressed(GamepadButtonType::DPadRight) {
+            step += GAMEPAD_STEP;
+        }
+        if just_pressed(GamepadButtonType::DPadLeft) {
+            step -= GAMEPAD_STEP;
+        }
+
+        let stick_x = axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or_default();
+        if stick_x.abs() > STICK_THRESHOLD {
+            stick_tilted = true;
+            if !*stick_held {
+                step += stick_x.signum() * GAMEPAD_STEP;
+            }
+        }
+    }
+    *stick_held = stick_tilted;
+    if step == 0.0 {
+        return;
+    }
+
+    let Some(mut slider) = focused.0.and_then(|entity| sliders.get_mut(entity).ok()) else {
+        return;
+    };
+    // Round so that repeated steps land exactly on 0 and 1.
+    let value = ((slider.value + step) / GAMEPAD_STEP).round() * GAMEPAD_STEP;
+    slider.value = value.clamp(0.0, 1.0);
+}
+
+fn update_slider_fill(
+    sliders: Query<(&Slider, &Children), Changed<Slider>>,
+    mut fills: Query<&mut Style, With<SliderFill>>,
+) {
+    for (slider, children) in &sliders {
+        let mut fills = fills.iter_many_mut(children);
+        while let Some(mut style) = fills.fetch_next() {
+            style.width = Val::Percent(slider.value * 100.0);
+        }
+    }
+}
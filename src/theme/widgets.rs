@@ -1,8 +1,16 @@
 //! Helper traits for creating common widgets.
 
-use bevy::{ecs::system::EntityCommands, prelude::*, ui::Val::*};
+use bevy::{
+    ecs::system::EntityCommands,
+    prelude::*,
+    ui::{RelativeCursorPosition, Val::*},
+};
 
-use crate::theme::{interaction::InteractionPalette, palette::*};
+use crate::theme::{
+    interaction::{Focusable, InteractionPalette},
+    palette::*,
+    slider::{Slider, SliderFill},
+};
 
 /// An extension trait for spawning UI widgets.
 pub trait Widgets {
@@ -19,6 +27,9 @@ pub trait Widgets {
     fn label(&mut self, text: impl Into<String>) -> EntityCommands;
 
     fn big_label(&mut self, text: impl Into<String>) -> EntityCommands;
+
+    /// Spawn a horizontal [`Slider`] starting at `value`.
+    fn slider(&mut self, value: f32) -> EntityCommands;
 }
 
 impl<T: Spawn> Widgets for T {
@@ -41,6 +52,7 @@ impl<T: Spawn> Widgets for T {
                 hovered: BUTTON_HOVERED_BACKGROUND,
                 pressed: BUTTON_PRESSED_BACKGROUND,
             },
+            Focusable,
         ));
         entity.with_children(|children| {
             children.spawn((
@@ -166,6 +178,46 @@ impl<T: Spawn> Widgets for T {
         ));
         entity
     }
+
+    fn slider(&mut self, value: f32) -> EntityCommands {
+        let mut entity = self.spawn((
+            Name::new("Slider"),
+            ButtonBundle {
+                style: Style {
+                    width: Px(300.0),
+                    height: Px(24.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(NODE_BACKGROUND),
+                ..default()
+            },
+            InteractionPalette {
+                none: NODE_BACKGROUND,
+                hovered: ITEM_HOVERED_BACKGROUND,
+                pressed: ITEM_PRESSED_BACKGROUND,
+            },
+            Focusable,
+            RelativeCursorPosition::default(),
+            Slider { value },
+        ));
+        entity.with_children(|children| {
+            children.spawn((
+                Name::new("Slider Fill"),
+                SliderFill,
+                NodeBundle {
+                    style: Style {
+                        width: Percent(value * 100.0),
+                        height: Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(BUTTON_HOVERED_BACKGROUND),
+                    ..default()
+                },
+            ));
+        });
+
+        entity
+    }
 }
 
 /// An extension trait for spawning UI containers.
@@ -0,0 +1,269 @@
+//! Transitions that cover the screen while the game switches between [`Screen`]s or areas, so
+//! that the entities of one don't pop out and the next pop in. A transition covers the screen,
+//! performs the switch while it is fully covered, and uncovers it again.
+//!
+//! Screen changes get a transition without their callers doing anything: requested changes of
+//! [`NextState<Screen>`] are held back until the screen is covered.
+
+use bevy::{prelude::*, ui::FocusPolicy};
+
+use crate::{game::movement::ActionsFrozen, screens::Screen, AppSet};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TransitionSettings>();
+    app.init_resource::<TransitionSettings>();
+    app.init_resource::<Transition>();
+
+    app.add_systems(PreUpdate, hold_back_screen_changes);
+    app.add_systems(
+        Update,
+        (advance_transition, update_overlay)
+            .chain()
+            .before(AppSet::TickTimers),
+    );
+}
+
+/// How the screen is covered and uncovered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum TransitionStyle {
+    /// Fade to black and back.
+    Fade,
+    /// Slide black over the screen from the left and off it to the right.
+    Wipe,
+    /// Close a circle around the center of the screen and open it again.
+    Iris,
+}
+
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+#[reflect(Resource)]
+pub struct TransitionSettings {
+    pub screen_style: TransitionStyle,
+    pub area_style: TransitionStyle,
+    /// How long covering and uncovering each take, in seconds.
+    pub fade_duration: f32,
+    /// How long the screen stays covered, in seconds.
+    pub hold_duration: f32,
+}
+
+impl Default for TransitionSettings {
+    fn default() -> Self {
+        Self {
+            screen_style: TransitionStyle::Fade,
+            area_style: TransitionStyle::Wipe,
+            fade_duration: 0.35,
+            hold_duration: 0.1,
+        }
+    }
+}
+
+impl TransitionSettings {
+    fn total_duration(&self) -> f32 {
+        2.0 * self.fade_duration + self.hold_duration
+    }
+}
+
+/// How much of something is shown at time `t` of an animation that fades it in over
+/// `fade_duration`, keeps it and fades it out over `fade_duration` again, between 0 and 1.
+pub fn fade_in_out(t: f32, total_duration: f32, fade_duration: f32) -> f32 {
+    // Normalize by duration.
+    let t = (t / total_duration).clamp(0.0, 1.0);
+    let fade = fade_duration / total_duration;
+
+    // Regular trapezoid-shaped graph, flat at the top with alpha = 1.0.
+    ((1.0 - (2.0 * t - 1.0).abs()) / fade).min(1.0)
+}
+
+type SwitchAction = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+/// The running transition, if any.
+#[derive(Resource, Default)]
+pub struct Transition {
+    running: Option<RunningTransition>,
+    /// The next screen change is the one of the running transition and may pass.
+    let_through: bool,
+}
+
+struct RunningTransition {
+    style: TransitionStyle,
+    /// Seconds since the transition started.
+    t: f32,
+    /// Performs the switch once the screen is covered, and is gone afterwards.
+    switch: Option<SwitchAction>,
+}
+
+impl Transition {
+    /// Cover the screen, run `switch` and uncover it again.
+    /// If a transition is still covering the screen, it runs `switch` instead of its own.
+    pub fn start(
+        &mut self,
+        style: TransitionStyle,
+        switch: impl FnOnce(&mut World) + Send + Sync + 'static,
+    ) {
+        match &mut self.running {
+            Some(running) if running.switch.is_some() => running.switch = Some(Box::new(switch)),
+            _ => {
+                self.running = Some(RunningTransition {
+                    style,
+                    t: 0.0,
+                    switch: Some(Box::new(switch)),
+                })
+            }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+}
+
+/// Runs before the state transitions, so that screen changes requested during the last update
+/// only happen once a transition covered the screen.
+fn hold_back_screen_changes(
+    settings: Res<TransitionSettings>,
+    screen: Res<State<Screen>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut transition: ResMut<Transition>,
+) {
+    let NextState::Pending(next) = *next_screen.as_ref() else {
+        return;
+    };
+    if std::mem::take(&mut transition.let_through) || next == *screen.get() {
+        return;
+    }
+    next_screen.reset();
+    transition.start(settings.screen_style, move |world: &mut World| {
+        world.resource_mut::<NextState<Screen>>().set(next);
+        world.resource_mut::<Transition>().let_through = true;
+    });
+}
+
+/// Transitions run in real time, so the game being paused doesn't stop them.
+fn advance_transition(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    settings: Res<TransitionSettings>,
+    mut transition: ResMut<Transition>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+) {
+    let in_transition = transition.is_running();
+    if actions_frozen.in_transition() != in_transition {
+        actions_frozen.set_in_transition(in_transition);
+    }
+    let Some(running) = &mut transition.running else {
+        return;
+    };
+    running.t += time.delta_seconds();
+    if running.t >= settings.total_duration() / 2.0 {
+        if let Some(switch) = running.switch.take() {
+            commands.add(switch);
+            // Uncover the screen in the same time after every switch, no matter how the frames
+            // before it lined up, so that replays unfreeze actions on the same frame.
+            running.t = settings.total_duration() / 2.0;
+        }
+    }
+    if running.t >= settings.total_duration() {
+        transition.running = None;
+    }
+}
+
+/// Covers the screen during a transition and catches clicks meant for the UI below.
+#[derive(Component)]
+struct Overlay;
+
+/// The black part of the [`Overlay`] for wipes and irises.
+#[derive(Component)]
+struct OverlayShape;
+
+/// Border of the iris around the hole in its middle, wide enough to cover the corners of the
+/// screen around the largest hole, in percent of the larger side of the window.
+const IRIS_BORDER: f32 = 80.0;
+/// Radius of the fully open iris.
+const IRIS_RADIUS: f32 = 75.0;
+
+fn update_overlay(
+    mut commands: Commands,
+    settings: Res<TransitionSettings>,
+    transition: Res<Transition>,
+    mut overlay: Query<(Entity, &mut BackgroundColor), (With<Overlay>, Without<OverlayShape>)>,
+    mut shape: Query<(&mut Style, &mut BackgroundColor, &mut BorderColor), With<OverlayShape>>,
+) {
+    let Some(running) = &transition.running else {
+        for (entity, _) in &overlay {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    };
+    let Ok((_, mut overlay_color)) = overlay.get_single_mut() else {
+        commands
+            .spawn((
+                Name::new("Transition Overlay"),
+                Overlay,
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::NONE),
+                    focus_policy: FocusPolicy::Block,
+                    z_index: ZIndex::Global(i32::MAX),
+                    ..default()
+                },
+            ))
+            .with_children(|children| {
+                children.spawn((
+                    Name::new("Transition Shape"),
+                    OverlayShape,
+                    NodeBundle {
+                        background_color: BackgroundColor(Color::NONE),
+                        border_color: BorderColor(Color::NONE),
+                        border_radius: BorderRadius::MAX,
+                        ..default()
+                    },
+                ));
+            });
+        return;
+    };
+    let Ok((mut style, mut shape_color, mut border_color)) = shape.get_single_mut() else {
+        return;
+    };
+
+    let total_duration = settings.total_duration();
+    let coverage = fade_in_out(running.t, total_duration, settings.fade_duration);
+    let covering = running.t < total_duration / 2.0;
+    overlay_color.0 = Color::NONE;
+    shape_color.0 = Color::NONE;
+    border_color.0 = Color::NONE;
+    match running.style {
+        TransitionStyle::Fade => {
+            overlay_color.0 = Color::BLACK.with_alpha(coverage);
+        }
+        TransitionStyle::Wipe => {
+            shape_color.0 = Color::BLACK;
+            *style = Style {
+                position_type: PositionType::Absolute,
+                height: Val::Percent(100.0),
+                width: Val::Percent(coverage * 100.0),
+                left: if covering { Val::Px(0.0) } else { Val::Auto },
+                right: if covering { Val::Auto } else { Val::Px(0.0) },
+                ..default()
+            };
+        }
+        TransitionStyle::Iris => {
+            border_color.0 = Color::BLACK;
+            let radius = (1.0 - coverage) * IRIS_RADIUS;
+            let size = Val::VMax(2.0 * (radius + IRIS_BORDER));
+            *style = Style {
+                width: size,
+                height: size,
+                flex_shrink: 0.0,
+                border: UiRect::all(Val::VMax(IRIS_BORDER)),
+                ..default()
+            };
+        }
+    }
+}